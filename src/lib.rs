@@ -40,7 +40,386 @@ fn main() {
 
 extern crate nalgebra as na;
 
-use na::Vec4;
+use na::{UnitQuat, Vec3, Vec4};
+use std::error::Error;
+use std::fmt;
+
+/// Find the segment containing distance `d` given the cumulative
+/// lengths of a polyline. Returns the segment index and the local
+/// parameter (between 0 and 1) within that segment.
+fn find_segment(lvalues: &[f32], d: f32) -> (usize, f32)
+{
+    let last = lvalues.len() - 2;
+    let index = lvalues[1..last+1].partition_point(|&l| l < d);
+    let delta = lvalues[index+1] - lvalues[index];
+    let mut t = 0.0;
+    if delta > 0.0 {
+        t = (d - lvalues[index]) / delta;
+    }
+    (index, t.clamp(0.0, 1.0))
+}
+
+/// Evenly spaced distances for `n` samples along a polyline of the
+/// given `length` (including both end points).
+fn spaced_distances(length: f32, n: usize) -> Vec<f32>
+{
+    let mut distances: Vec<f32> = Vec::with_capacity(n);
+    let mut step = 0.0;
+    if n > 1 {
+        step = length / ((n-1) as f32);
+    }
+    for i in 0..n {
+        distances.push(step * (i as f32));
+    }
+    distances
+}
+
+/// Parameter (between 0 and 1) of the point on the segment from `a`
+/// to `b` which is closest to `q`.
+fn project_segment(a: Vec4<f32>, b: Vec4<f32>, q: Vec4<f32>) -> f32
+{
+    let direction: Vec4<f32> = b - a;
+    let sqlength = na::sqnorm(&direction);
+    if sqlength > 0.0 {
+        (na::dot(&(q - a), &direction) / sqlength).clamp(0.0, 1.0)
+    } else {
+        0.0
+    }
+}
+
+/// Derivative (with respect to `t`) of the Catmull-Rom spline segment
+/// from `p1` to `p2`.
+fn catmull_rom_derivative(p0: Vec4<f32>, p1: Vec4<f32>, p2: Vec4<f32>,
+                          p3: Vec4<f32>, t: f32) -> Vec4<f32>
+{
+    ((p2 - p0) +
+     (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * (2.0 * t) +
+     (p1 * 3.0 - p0 - p2 * 3.0 + p3) * (3.0 * t * t)) * 0.5
+}
+
+/// Shortest rotation turning the direction `u` into the direction `v`.
+fn rotation_between(u: Vec3<f32>, v: Vec3<f32>) -> UnitQuat<f32>
+{
+    let (nu, nv) = (na::norm(&u), na::norm(&v));
+    if nu <= 0.0 || nv <= 0.0 {
+        return na::one();
+    }
+    let (u, v) = (u / nu, v / nv);
+    let mut axis = na::cross(&u, &v);
+    let angle = na::norm(&axis).atan2(na::dot(&u, &v));
+    if na::norm(&axis) < 1.0e-6 {
+        if angle < 1.0 {
+            return na::one();
+        }
+        // opposite directions, turn around any perpendicular axis
+        axis = na::cross(&u, &Vec3::new(1.0, 0.0, 0.0));
+        if na::norm(&axis) < 1.0e-3 {
+            axis = na::cross(&u, &Vec3::new(0.0, 1.0, 0.0));
+        }
+    }
+    UnitQuat::new(na::normalize(&axis) * angle)
+}
+
+/// Area of the triangle with the corners `a`, `b`, and `c`.
+fn triangle_area(a: Vec4<f32>, b: Vec4<f32>, c: Vec4<f32>) -> f32
+{
+    let u: Vec4<f32> = a - b;
+    let v: Vec4<f32> = c - b;
+    let dot = na::dot(&u, &v);
+    0.5 * (na::sqnorm(&u) * na::sqnorm(&v) - dot * dot).max(0.0).sqrt()
+}
+
+/// Map `value` from the range between `min` and `max` to the unit
+/// interval (0.5 for an empty range).
+fn unit_interval(value: f32, min: f32, max: f32) -> f32
+{
+    if max > min {
+        (value - min) / (max - min)
+    } else {
+        0.5
+    }
+}
+
+/// Next value of the SplitMix64 pseudo-random number generator.
+fn splitmix64(state: &mut u64) -> u64
+{
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Z component of the cross product of `a - o` and `b - o` (within the
+/// XY plane), positive if `o`, `a`, `b` turn counter-clockwise.
+fn cross_2d(o: Vec4<f32>, a: Vec4<f32>, b: Vec4<f32>) -> f32
+{
+    (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x)
+}
+
+/// Signed angle (within the XY plane) between the segments from `a` to
+/// `b` and from `b` to `c`, positive for left turns.
+fn turn_angle_2d(a: Vec4<f32>, b: Vec4<f32>, c: Vec4<f32>) -> f32
+{
+    let dot = (b.x - a.x) * (c.x - b.x) + (b.y - a.y) * (c.y - b.y);
+    cross_2d(a, b, c).atan2(dot)
+}
+
+/// Smallest circle (within the XY plane) through `a` and `b`. Returns
+/// the center (with z = 0 and w = 1) and the radius.
+fn circle_2d(a: Vec4<f32>, b: Vec4<f32>) -> (Vec4<f32>, f32)
+{
+    let center = Vec4::new((a.x + b.x) * 0.5, (a.y + b.y) * 0.5, 0.0, 1.0);
+    (center, distance_2d(center, a))
+}
+
+/// Circle (within the XY plane) through `a`, `b`, and `c`. For
+/// (nearly) collinear points the smallest circle containing all of
+/// them is returned instead.
+fn circumcircle_2d(a: Vec4<f32>, b: Vec4<f32>, c: Vec4<f32>) -> (Vec4<f32>, f32)
+{
+    let d = 2.0 * cross_2d(a, b, c);
+    if d.abs() <= f32::EPSILON {
+        let mut circle = circle_2d(a, b);
+        for candidate in [circle_2d(a, c), circle_2d(b, c)].iter() {
+            if candidate.1 > circle.1 {
+                circle = *candidate;
+            }
+        }
+        return circle;
+    }
+    let (bx, by) = (b.x - a.x, b.y - a.y);
+    let (cx, cy) = (c.x - a.x, c.y - a.y);
+    let b2 = bx * bx + by * by;
+    let c2 = cx * cx + cy * cy;
+    let center = Vec4::new(a.x + (cy * b2 - by * c2) / d,
+                           a.y + (bx * c2 - cx * b2) / d,
+                           0.0, 1.0);
+    (center, distance_2d(center, a))
+}
+
+/// Distance between `a` and `b` within the XY plane.
+fn distance_2d(a: Vec4<f32>, b: Vec4<f32>) -> f32
+{
+    ((a.x - b.x) * (a.x - b.x) + (a.y - b.y) * (a.y - b.y)).sqrt()
+}
+
+/// Clip the segment from `a` to `b` against the axis-aligned box
+/// spanned by `min` and `max` (w is ignored). Returns the parameter
+/// range of the segment inside the box, if any.
+fn clip_segment(a: Vec4<f32>, b: Vec4<f32>, min: Vec4<f32>, max: Vec4<f32>)
+                -> Option<(f32, f32)>
+{
+    let mut t0 = 0.0f32;
+    let mut t1 = 1.0f32;
+    for &(p, q, low, high) in &[(a.x, b.x, min.x, max.x),
+                                 (a.y, b.y, min.y, max.y),
+                                 (a.z, b.z, min.z, max.z)] {
+        let delta = q - p;
+        if delta == 0.0 {
+            if p < low || p > high {
+                return None;
+            }
+            continue;
+        }
+        let (mut s0, mut s1) = ((low - p) / delta, (high - p) / delta);
+        if s0 > s1 {
+            std::mem::swap(&mut s0, &mut s1);
+        }
+        t0 = t0.max(s0);
+        t1 = t1.min(s1);
+    }
+    if t1 >= t0 {
+        Some((t0, t1))
+    } else {
+        None
+    }
+}
+
+/// Distance from `p` to the axis-aligned box spanned by `min` and
+/// `max` (w is ignored), 0 inside the box.
+fn aabb_distance(p: Vec4<f32>, min: Vec4<f32>, max: Vec4<f32>) -> f32
+{
+    let dx = (min.x - p.x).max(p.x - max.x).max(0.0);
+    let dy = (min.y - p.y).max(p.y - max.y).max(0.0);
+    let dz = (min.z - p.z).max(p.z - max.z).max(0.0);
+    (dx * dx + dy * dy + dz * dz).sqrt()
+}
+
+/// Discrete Frechet distance between the points of `a` and `b` (the
+/// shortest leash needed to walk both polylines point by point in
+/// order), `f32::MAX` if one of them is empty.
+fn discrete_frechet(a: &[Vec4<f32>], b: &[Vec4<f32>]) -> f32
+{
+    if a.is_empty() || b.is_empty() {
+        return f32::MAX;
+    }
+    // one row of the coupling table at a time
+    let mut previous: Vec<f32> = vec![f32::MAX; b.len()];
+    let mut current: Vec<f32> = vec![0.0; b.len()];
+    for (i, p) in a.iter().enumerate() {
+        for (j, q) in b.iter().enumerate() {
+            let vector: Vec4<f32> = *p - *q;
+            let dist = na::norm(&vector);
+            let reachable = if i == 0 && j == 0 {
+                0.0
+            } else if i == 0 {
+                current[j-1]
+            } else if j == 0 {
+                previous[j]
+            } else {
+                previous[j].min(previous[j-1]).min(current[j-1])
+            };
+            current[j] = dist.max(reachable);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()-1]
+}
+
+/// Closest points between the segment from `p1` to `q1` and the
+/// segment from `p2` to `q2`.
+fn closest_segments(p1: Vec4<f32>, q1: Vec4<f32>, p2: Vec4<f32>, q2: Vec4<f32>)
+                    -> (Vec4<f32>, Vec4<f32>)
+{
+    let d1: Vec4<f32> = q1 - p1;
+    let d2: Vec4<f32> = q2 - p2;
+    let r: Vec4<f32> = p1 - p2;
+    let a = na::dot(&d1, &d1);
+    let e = na::dot(&d2, &d2);
+    let f = na::dot(&d2, &r);
+    let mut s = 0.0;
+    let mut t = 0.0;
+    if a <= 0.0 && e > 0.0 {
+        t = (f / e).clamp(0.0, 1.0);
+    } else if a > 0.0 {
+        let c = na::dot(&d1, &r);
+        if e <= 0.0 {
+            s = (-c / a).clamp(0.0, 1.0);
+        } else {
+            // both segments have a length
+            let b = na::dot(&d1, &d2);
+            let denom = a * e - b * b;
+            if denom > 0.0 {
+                s = ((b * f - c * e) / denom).clamp(0.0, 1.0);
+            }
+            t = (b * s + f) / e;
+            if t < 0.0 {
+                t = 0.0;
+                s = (-c / a).clamp(0.0, 1.0);
+            } else if t > 1.0 {
+                t = 1.0;
+                s = ((b - c) / a).clamp(0.0, 1.0);
+            }
+        }
+    }
+    (p1 + d1 * s, p2 + d2 * t)
+}
+
+/// Parameter (between 0 and 1) of the point on the segment from `a`
+/// to `b` which is closest to `q` (using only the XY components).
+fn project_2d(a: Vec4<f32>, b: Vec4<f32>, q: Vec4<f32>) -> f32
+{
+    let dx = b.x - a.x;
+    let dy = b.y - a.y;
+    let sqlength = dx * dx + dy * dy;
+    if sqlength > 0.0 {
+        (((q.x - a.x) * dx + (q.y - a.y) * dy) / sqlength).clamp(0.0, 1.0)
+    } else {
+        0.0
+    }
+}
+
+/// Perpendicular distance from `p` to the (infinite) line through
+/// `a` and `b` (or the distance to `a` if both coincide).
+fn line_distance(a: Vec4<f32>, b: Vec4<f32>, p: Vec4<f32>) -> f32
+{
+    let direction: Vec4<f32> = b - a;
+    let vector: Vec4<f32> = p - a;
+    let sqlength = na::sqnorm(&direction);
+    if sqlength > 0.0 {
+        let projected = direction * (na::dot(&vector, &direction) / sqlength);
+        na::norm(&(vector - projected))
+    } else {
+        na::norm(&vector)
+    }
+}
+
+/// Evaluate a uniform Catmull-Rom segment between `p1` and `p2` at
+/// local parameter `t` (between 0 and 1).
+fn catmull_rom(p0: Vec4<f32>, p1: Vec4<f32>, p2: Vec4<f32>, p3: Vec4<f32>,
+               t: f32) -> Vec4<f32>
+{
+    let t2 = t * t;
+    let t3 = t2 * t;
+    (p1 * 2.0 +
+     (p2 - p0) * t +
+     (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * t2 +
+     (p1 * 3.0 - p0 - p2 * 3.0 + p3) * t3) * 0.5
+}
+
+/// Drop the `w` component.
+fn xyz(v: Vec4<f32>) -> Vec3<f32>
+{
+    Vec3::new(v.x, v.y, v.z)
+}
+
+/// Reflect `v` on the plane with normal `n` (where `c` is the squared
+/// length of `n`).
+fn reflect(v: Vec3<f32>, n: Vec3<f32>, c: f32) -> Vec3<f32>
+{
+    if c > 0.0 {
+        v - n * (2.0 / c * na::dot(&n, &v))
+    } else {
+        v
+    }
+}
+
+/// Conversion into the points stored by a Path, so that e.g. tuples
+/// can be passed to `PathBuilder::add_point` directly.
+pub trait IntoPoint {
+    /// Convert into a point.
+    fn into_point(self) -> Vec4<f32>;
+}
+
+impl IntoPoint for Vec4<f32> {
+    fn into_point(self) -> Vec4<f32>
+    {
+        self
+    }
+}
+
+impl IntoPoint for (f32, f32, f32, f32) {
+    fn into_point(self) -> Vec4<f32>
+    {
+        Vec4::new(self.0, self.1, self.2, self.3)
+    }
+}
+
+impl IntoPoint for (f32, f32, f32) {
+    /// The `w` component is set to 1.
+    fn into_point(self) -> Vec4<f32>
+    {
+        Vec4::new(self.0, self.1, self.2, 1.0)
+    }
+}
+
+/// Coordinate axes (e.g. to choose a plane for mirroring).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+/// Number of problems fixed by `Path::sanitize`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SanitizeReport {
+    /// Points with a NaN or infinite component which were dropped.
+    pub non_finite: usize,
+    /// Points within epsilon of the previous point which were dropped.
+    pub duplicates: usize,
+}
 
 #[derive(Debug)]
 pub struct Path {
@@ -48,37 +427,91 @@ pub struct Path {
     lvalues: Vec<f32>,
     changed: bool,
     current_length: f32,
+    params: Option<Vec<f32>>,
+    original_point_count: usize,
 }
 
 impl Path {
+    /// Create a path from points which are already in order.
+    fn from_points(points: Vec<Vec4<f32>>) -> Path
+    {
+        let original_point_count = points.len();
+        Path { points,
+               lvalues: Vec::new(),
+               changed: true,
+               current_length: 0.0f32,
+               params: None,
+               original_point_count, }
+    }
     /// Distribute `numpts` many points along a polyline.
     pub fn evaluate(&mut self, numpts: u8) -> Vec<Vec4<f32>>
     {
-        let mut points: Vec<Vec4<f32>> = Vec::new();
-        let length = self.length();
-        let step = length / ((numpts-1) as f32);
-        let mut current_length: f32 = 0.0;
-        let mut index = 0;
-        for _i in 0..numpts {
-            while index + 1 != self.lvalues.len() &&
-                current_length > self.lvalues[index+1] {
-                index += 1;
-            }
-            if (index + 1) == self.lvalues.len() {
-                index -= 1;
+        // fill the cache
+        self.length();
+        self.evaluate_with(numpts as usize, |p1, p2, t| p1 * (1.0 - t) + p2 * t)
+    }
+    /// Distribute `n` points along a polyline (like `evaluate`), but
+    /// blend the two end points of the segment containing each point
+    /// with `interp` (called with the local parameter between 0 and 1
+    /// within that segment).
+    pub fn evaluate_with<F>(&self, n: usize, interp: F) -> Vec<Vec4<f32>>
+        where F: Fn(Vec4<f32>, Vec4<f32>, f32) -> Vec4<f32>
+    {
+        let mut points: Vec<Vec4<f32>> = Vec::with_capacity(n);
+        if self.points.len() < 2 {
+            // nothing to distribute, repeat a single point
+            if let Some(&point) = self.points.first() {
+                for _i in 0..n {
+                    points.push(point);
+                }
             }
-            // divide found distance
-            let p1 = self.points[index];
-            let p2 = self.points[index+1];
-            let a = current_length - self.lvalues[index];
-            let b = self.lvalues[index+1] - current_length;
-            let point = (p2 * a + p1 * b) /
-                (self.lvalues[index+1] - self.lvalues[index]);
-            points.push(point);
-            current_length += step;
+            return points;
+        }
+        let lvalues = self.cumulative_lengths();
+        for d in spaced_distances(lvalues[lvalues.len()-1], n) {
+            let (index, t) = find_segment(&lvalues, d);
+            points.push(interp(self.points[index], self.points[index+1], t));
         }
         points
     }
+    /// Distribute `n` points along a polyline (like `evaluate`), each
+    /// paired with the index of the segment it was interpolated on.
+    pub fn evaluate_with_segments(&self, n: usize) -> Vec<(Vec4<f32>, usize)>
+    {
+        let mut samples: Vec<(Vec4<f32>, usize)> = Vec::with_capacity(n);
+        if self.points.len() < 2 {
+            if let Some(&point) = self.points.first() {
+                for _i in 0..n {
+                    samples.push((point, 0));
+                }
+            }
+            return samples;
+        }
+        let lvalues = self.cumulative_lengths();
+        for d in spaced_distances(lvalues[lvalues.len()-1], n) {
+            let (index, t) = find_segment(&lvalues, d);
+            let point = self.points[index] * (1.0 - t) + self.points[index+1] * t;
+            samples.push((point, index));
+        }
+        samples
+    }
+    /// Distribute `n` points along a polyline (like `evaluate`) and map
+    /// their x and y into the unit square, relative to the axis-aligned
+    /// bounding box of the polyline. Sides of the box without extent
+    /// map to 0.5.
+    pub fn evaluate_normalized_2d(&self, n: usize) -> Vec<(f32, f32)>
+    {
+        let mut coords: Vec<(f32, f32)> = Vec::with_capacity(n);
+        if self.points.is_empty() {
+            return coords;
+        }
+        let (min, max) = self.aabb();
+        for point in self.sample(n) {
+            coords.push((unit_interval(point.x, min.x, max.x),
+                         unit_interval(point.y, min.y, max.y)));
+        }
+        coords
+    }
     /// Calculate the length of a polyline by summing up the length of
     /// all individual vectors.
     pub fn length(&mut self) -> f32
@@ -98,77 +531,4782 @@ impl Path {
         }
         length
     }
-}
-
-/// Helper to construct a Path.
-
-pub struct PathBuilder {
-    points: Vec<Vec4<f32>>,
-    sorted: Vec<Vec4<f32>>,
-    params: Vec<f32>,
-}
-
-impl PathBuilder {
-    /// Prepares the creation of a path. Points can be added either in
-    /// order or by providing an additional parameter (which creates
-    /// that order once all points were added).
-    pub fn new() -> PathBuilder
+    /// The length stored in the cache (without `&mut` access), or
+    /// `None` if the cache is outdated and `length` has to be called.
+    pub fn cached_length(&self) -> Option<f32>
     {
-        PathBuilder { points: Vec::new(),
-                      sorted: Vec::new(),
-                      params: Vec::new(),}
+        if self.changed {
+            None
+        } else {
+            Some(self.current_length)
+        }
     }
-    /// Add points in a particular order by repeatedly calling this
-    /// function.
-    pub fn add_point(&mut self, point: Vec4<f32>) ->
-        &mut PathBuilder
+    /// Calculate the length of a polyline (like `length`) in a single
+    /// pass, without storing the cumulative lengths in the cache.
+    pub fn length_streaming(&self) -> f32
     {
-        self.points.push(point);
-        self
+        let mut length = 0.0;
+        for i in 1..self.points.len() {
+            let vector: Vec4<f32> = self.points[i] - self.points[i-1];
+            length += na::norm(&vector);
+        }
+        length
     }
-    /// Add points by calling this function repeatedly in any order,
-    /// but implicitly defining an order by providing a parameter for
-    /// each point.
-    pub fn add_sorted_point(&mut self, point: Vec4<f32>, param: f32) ->
-        &mut PathBuilder
+    /// Approximate the length of a polyline by only using every
+    /// `stride`-th point (and always the last one). A `stride` of 1
+    /// gives the exact length.
+    pub fn length_approx(&self, stride: usize) -> f32
     {
-        if self.sorted.is_empty() {
-            self.sorted.push(point);
-            self.params.push(param);
-        } else {
-            // find index to insert param
-            let mut was_inserted = false;
-            for i in 0..self.params.len() {
-                if param < self.params[i] {
-                    self.sorted.insert(i, point);
-                    self.params.insert(i, param);
-                    was_inserted = true;
-                    break;
+        let stride = stride.max(1);
+        let mut length = 0.0;
+        if self.points.is_empty() {
+            return length;
+        }
+        let last = self.points.len() - 1;
+        let mut previous = 0;
+        while previous < last {
+            let next = (previous + stride).min(last);
+            let vector: Vec4<f32> = self.points[next] - self.points[previous];
+            length += na::norm(&vector);
+            previous = next;
+        }
+        length
+    }
+    /// Calculate the length of the polyline between the points with
+    /// indices `i` and `j` (in any order). Panics if either index is
+    /// out of bounds.
+    pub fn length_between(&self, i: usize, j: usize) -> f32
+    {
+        assert!(i < self.points.len() && j < self.points.len(),
+                "point index out of bounds");
+        if !self.changed {
+            return (self.lvalues[j] - self.lvalues[i]).abs();
+        }
+        let mut length = 0.0;
+        for k in (i.min(j)+1)..(i.max(j)+1) {
+            let vector: Vec4<f32> = self.points[k] - self.points[k-1];
+            length += na::norm(&vector);
+        }
+        length
+    }
+    /// Calculate the length of a polyline projected onto the XY plane
+    /// (ignoring z and w).
+    pub fn length_2d(&self) -> f32
+    {
+        let mut length = 0.0;
+        for i in 1..self.points.len() {
+            let dx = self.points[i].x - self.points[i-1].x;
+            let dy = self.points[i].y - self.points[i-1].y;
+            length += (dx * dx + dy * dy).sqrt();
+        }
+        length
+    }
+    /// Perimeter (including the closing segment back to the first
+    /// point) and enclosed (absolute) area of the polyline treated as a
+    /// closed polygon within the XY plane.
+    pub fn closed_metrics_2d(&self) -> (f32, f32)
+    {
+        let m = self.points.len();
+        let mut perimeter = 0.0;
+        let mut area = 0.0;
+        for i in 0..m {
+            let a = self.points[i];
+            let b = self.points[(i+1) % m];
+            perimeter += distance_2d(a, b);
+            area += a.x * b.y - b.x * a.y;
+        }
+        (perimeter, 0.5 * area.abs())
+    }
+    /// Sum up all increases of z (e.g. elevation) along the polyline.
+    pub fn elevation_gain(&self) -> f32
+    {
+        let mut gain = 0.0;
+        for i in 1..self.points.len() {
+            let dz = self.points[i].z - self.points[i-1].z;
+            if dz > 0.0 {
+                gain += dz;
+            }
+        }
+        gain
+    }
+    /// Sum up all decreases of z (e.g. elevation) along the polyline
+    /// (as a positive value).
+    pub fn elevation_loss(&self) -> f32
+    {
+        let mut loss = 0.0;
+        for i in 1..self.points.len() {
+            let dz = self.points[i].z - self.points[i-1].z;
+            if dz < 0.0 {
+                loss -= dz;
+            }
+        }
+        loss
+    }
+    /// Check whether the component `axis` (0 for x, 1 for y and 2 for
+    /// z) never decreases from one point to the next. Panics for any
+    /// other `axis`.
+    pub fn is_monotonic_along(&self, axis: usize) -> bool
+    {
+        assert!(axis < 3, "invalid axis {} (expected 0, 1 or 2)", axis);
+        let component = |point: &Vec4<f32>| match axis {
+            0 => point.x,
+            1 => point.y,
+            _ => point.z,
+        };
+        self.points.windows(2)
+            .all(|pair| component(&pair[1]) >= component(&pair[0]))
+    }
+    /// Sum up all changes (ascent and descent) of z along the polyline.
+    pub fn total_elevation_change(&self) -> f32
+    {
+        let mut change = 0.0;
+        for i in 1..self.points.len() {
+            change += (self.points[i].z - self.points[i-1].z).abs();
+        }
+        change
+    }
+    /// Calculate the average length of all segments of a polyline.
+    pub fn average_segment_length(&self) -> f32
+    {
+        if self.points.len() < 2 {
+            return 0.0;
+        }
+        let lvalues = self.cumulative_lengths();
+        lvalues[lvalues.len()-1] / ((self.points.len()-1) as f32)
+    }
+    /// Find the shortest and the longest segment of a polyline. Returns
+    /// both lengths followed by the indices of those two segments.
+    pub fn segment_extents(&self) -> (f32, f32, usize, usize)
+    {
+        let mut shortest: (f32, usize) = (0.0, 0);
+        let mut longest: (f32, usize) = (0.0, 0);
+        for i in 1..self.points.len() {
+            let vector: Vec4<f32> = self.points[i] - self.points[i-1];
+            let length = na::norm(&vector);
+            if i == 1 || length < shortest.0 {
+                shortest = (length, i-1);
+            }
+            if i == 1 || length > longest.0 {
+                longest = (length, i-1);
+            }
+        }
+        (shortest.0, longest.0, shortest.1, longest.1)
+    }
+    /// Lengths of all individual segments of a polyline.
+    pub fn segment_lengths(&self) -> Vec<f32>
+    {
+        let mut lengths: Vec<f32> = Vec::new();
+        for i in 1..self.points.len() {
+            let vector: Vec4<f32> = self.points[i] - self.points[i-1];
+            lengths.push(na::norm(&vector));
+        }
+        lengths
+    }
+    /// Lengths of all individual segments divided by the length of the
+    /// polyline (adding up to 1, all 0 if it has no length).
+    pub fn segment_length_fractions(&self) -> Vec<f32>
+    {
+        let mut lengths = self.segment_lengths();
+        let length: f32 = lengths.iter().sum();
+        if length > 0.0 {
+            for l in &mut lengths {
+                *l /= length;
+            }
+        }
+        lengths
+    }
+    /// Discrete (Menger) curvature at point `index`: the inverse radius
+    /// of the circle through the point and its two neighbours. It's 0
+    /// at both end points (and for collinear neighbours).
+    pub fn curvature_at_vertex(&self, index: usize) -> f32
+    {
+        if index == 0 || index + 1 >= self.points.len() {
+            return 0.0;
+        }
+        let a: Vec4<f32> = self.points[index-1] - self.points[index];
+        let b: Vec4<f32> = self.points[index+1] - self.points[index];
+        let c: Vec4<f32> = self.points[index+1] - self.points[index-1];
+        let lengths = na::norm(&a) * na::norm(&b) * na::norm(&c);
+        if lengths <= 0.0 {
+            return 0.0;
+        }
+        // twice the area of the triangle
+        let dot = na::dot(&a, &b);
+        let area2 = (na::sqnorm(&a) * na::sqnorm(&b) - dot * dot).max(0.0).sqrt();
+        2.0 * area2 / lengths
+    }
+    /// Interior point with the highest (Menger) curvature together with
+    /// that curvature, `None` for less than three points.
+    pub fn sharpest_corner(&self) -> Option<(usize, f32)>
+    {
+        let mut sharpest: Option<(usize, f32)> = None;
+        for i in 1..(self.points.len().max(1) - 1) {
+            let curvature = self.curvature_at_vertex(i);
+            match sharpest {
+                Some((_, max)) if max >= curvature => {}
+                _ => sharpest = Some((i, curvature)),
+            }
+        }
+        sharpest
+    }
+    /// Interior point whose removal changes the polyline least, the one
+    /// with the smallest effective area (the area of the triangle with
+    /// its two neighbours) as used by Visvalingam-Whyatt simplification.
+    /// `None` for less than three points.
+    pub fn least_significant_vertex(&self) -> Option<usize>
+    {
+        let mut least: Option<(usize, f32)> = None;
+        for i in 1..(self.points.len().max(1) - 1) {
+            let area = triangle_area(self.points[i-1], self.points[i],
+                                     self.points[i+1]);
+            match least {
+                Some((_, min)) if min <= area => {}
+                _ => least = Some((i, area)),
+            }
+        }
+        least.map(|(i, _area)| i)
+    }
+    /// Longest run of consecutive segments with all turning angles in
+    /// between below `angle_tol` (in radians). Returns the indices of
+    /// the first and the last point of the run and its length.
+    pub fn longest_straight_run(&self, angle_tol: f32) -> (usize, usize, f32)
+    {
+        let mut best = (0, 0, 0.0);
+        let m = self.points.len();
+        let mut start = 0;
+        let mut length = 0.0;
+        for i in 1..m {
+            if i > 1 {
+                let cos = na::dot(&self.segment_direction(i-2),
+                                  &self.segment_direction(i-1));
+                if cos.clamp(-1.0, 1.0).acos() >= angle_tol {
+                    // the run ends at the previous point
+                    start = i - 1;
+                    length = 0.0;
                 }
             }
-            if !was_inserted {
-                // append to end
-                self.sorted.push(point);
-                self.params.push(param);
+            let vector: Vec4<f32> = self.points[i] - self.points[i-1];
+            length += na::norm(&vector);
+            if length > best.2 {
+                best = (start, i, length);
             }
         }
-        self
+        best
     }
-    /// Use either points which were added in that particular order or
-    /// use provided parameters to sort points added in arbitrary
-    /// order.
-    pub fn finalize(self) -> Path
+    /// Smallest radius of curvature along the polyline (the inverse of
+    /// the highest curvature, see `sharpest_corner`), infinite for a
+    /// straight polyline.
+    pub fn min_turning_radius(&self) -> f32
     {
-        if self.params.is_empty() {
-            Path { points: self.points,
-                   lvalues: Vec::new(),
-                   changed: true,
-                   current_length: 0.0f32, }
-        } else {
-            Path { points: self.sorted,
-                   lvalues: Vec::new(),
-                   changed: true,
-                   current_length: 0.0f32, }
+        match self.sharpest_corner() {
+            Some((_index, curvature)) if curvature > 0.0 => 1.0 / curvature,
+            _ => f32::INFINITY,
+        }
+    }
+    /// Perpendicular distance of each interior point from the line
+    /// through its two neighbours (the measure used by Douglas-Peucker
+    /// simplification).
+    pub fn vertex_deviations(&self) -> Vec<f32>
+    {
+        let mut deviations: Vec<f32> = Vec::new();
+        for i in 2..self.points.len() {
+            deviations.push(line_distance(self.points[i-2],
+                                          self.points[i],
+                                          self.points[i-1]));
+        }
+        deviations
+    }
+    /// Signed turning angle (in radians, within the XY plane) at each
+    /// interior point, positive for left turns. For closed paths (where
+    /// the last point repeats the first one) the turn at the closing
+    /// point is appended, so the sum divided by 2 pi gives the winding
+    /// number.
+    pub fn signed_turn_angles_2d(&self) -> Vec<f32>
+    {
+        let mut angles: Vec<f32> = Vec::new();
+        let last = self.points.len().max(1) - 1;
+        let closed = last > 1 && self.points[0] == self.points[last];
+        for i in 1..last {
+            angles.push(turn_angle_2d(self.points[i-1], self.points[i],
+                                      self.points[i+1]));
+        }
+        if closed {
+            angles.push(turn_angle_2d(self.points[last-1], self.points[0],
+                                      self.points[1]));
+        }
+        angles
+    }
+    /// Discrete bending (elastica) energy of the polyline: the squared
+    /// (Menger) curvature at each interior point, weighted by half the
+    /// length of both adjacent segments, approximating the integral of
+    /// the squared curvature.
+    pub fn bending_energy(&self) -> f32
+    {
+        let mut energy = 0.0;
+        for i in 1..(self.points.len().max(1) - 1) {
+            let curvature = self.curvature_at_vertex(i);
+            let incoming: Vec4<f32> = self.points[i] - self.points[i-1];
+            let outgoing: Vec4<f32> = self.points[i+1] - self.points[i];
+            let weight = 0.5 * (na::norm(&incoming) + na::norm(&outgoing));
+            energy += curvature * curvature * weight;
+        }
+        energy
+    }
+    /// Smoothness energy of the polyline: the sum of `1 - cos(angle)`
+    /// over the turning angles at all interior points (0 for a straight
+    /// polyline). Segments without length are skipped.
+    pub fn kink_energy(&self) -> f32
+    {
+        let mut energy = 0.0;
+        for i in 1..(self.points.len().max(1) - 1) {
+            let incoming = self.segment_direction(i-1);
+            let outgoing = self.segment_direction(i);
+            if na::sqnorm(&incoming) > 0.0 && na::sqnorm(&outgoing) > 0.0 {
+                energy += 1.0 - na::dot(&incoming, &outgoing);
+            }
+        }
+        energy
+    }
+    /// Number of evenly spaced samples needed (see `evaluate`) so that
+    /// the chords between consecutive samples stay within `max_error`
+    /// of the polyline. The error only arises where a chord cuts a
+    /// corner, so the sharpest turning angle decides the spacing. No
+    /// number of samples meets a `max_error` of 0 (or below) at a
+    /// corner, `usize::MAX` is returned then.
+    pub fn samples_for_chord_error(&self, max_error: f32) -> usize
+    {
+        if self.points.len() < 2 {
+            return self.points.len();
+        }
+        let mut sharpest = 0.0f32;
+        for i in 1..(self.points.len() - 1) {
+            let cos = na::dot(&self.segment_direction(i-1),
+                              &self.segment_direction(i));
+            sharpest = sharpest.max(cos.clamp(-1.0, 1.0).acos());
+        }
+        let sin = (0.5 * sharpest).sin();
+        if sin <= 0.0 {
+            return 2;
+        }
+        if max_error <= 0.0 || max_error.is_nan() {
+            return usize::MAX;
+        }
+        // a corner halfway along a chord of length h is h/2 sin(angle/2)
+        // away from it
+        let spacing = 2.0 * max_error / sin;
+        ((self.length_streaming() / spacing).ceil() as usize).saturating_add(1)
+    }
+    /// Turning number (within the XY plane) of the polyline treated as
+    /// a closed loop: the sum of all signed turning angles (including
+    /// the ones at the closing segment) divided by 2 pi.
+    pub fn turning_number(&self) -> i32
+    {
+        let mut m = self.points.len();
+        if m > 1 && self.points[0] == self.points[m-1] {
+            // don't count the repeated point
+            m -= 1;
+        }
+        if m < 3 {
+            return 0;
+        }
+        let mut total = 0.0;
+        for i in 0..m {
+            total += turn_angle_2d(self.points[(i+m-1) % m], self.points[i],
+                                   self.points[(i+1) % m]);
         }
+        (total / (2.0 * std::f32::consts::PI)).round() as i32
+    }
+    /// Locate distance `d` along the polyline. Returns the index of the
+    /// segment containing that distance and the local parameter (between
+    /// 0 and 1) within that segment.
+    pub fn locate(&self, d: f32) -> (usize, f32)
+    {
+        if self.points.len() < 2 {
+            return (0, 0.0);
+        }
+        find_segment(&self.cumulative_lengths(), d)
+    }
+    /// Find the (original) point whose distance along the polyline is
+    /// closest to `d`. Returns its index together with the point (or
+    /// `None` for an empty path).
+    pub fn nearest_vertex_to_distance(&self, d: f32)
+                                      -> Option<(usize, Vec4<f32>)>
+    {
+        if self.points.is_empty() {
+            return None;
+        }
+        let lvalues = self.cumulative_lengths();
+        let mut index = 0;
+        for i in 1..self.points.len() {
+            if (lvalues[i] - d).abs() < (lvalues[index] - d).abs() {
+                index = i;
+            }
+        }
+        Some((index, self.points[index]))
+    }
+    /// Distribute `n` points along a polyline, but (unlike
+    /// `evaluate`) strictly between both end points.
+    pub fn evaluate_interior(&self, n: usize) -> Vec<Vec4<f32>>
+    {
+        let mut points: Vec<Vec4<f32>> = Vec::new();
+        if self.points.is_empty() {
+            return points;
+        }
+        let lvalues = self.cumulative_lengths();
+        let step = lvalues[lvalues.len()-1] / ((n+1) as f32);
+        for i in 1..(n+1) {
+            points.push(self.interpolate(&lvalues, step * (i as f32)));
+        }
+        points
+    }
+    /// Distribute `n` points along a polyline like `evaluate`, but
+    /// treat the `w` component of each point as a rational weight
+    /// (rational linear interpolation in homogeneous coordinates). The
+    /// resulting points are pulled towards points with heavy weights
+    /// and carry the interpolated weight in `w`. The spacing only
+    /// depends on the `xyz` components. Weights have to be positive,
+    /// an empty vector is returned if any weight is not.
+    pub fn evaluate_rational(&self, n: usize) -> Vec<Vec4<f32>>
+    {
+        let mut points: Vec<Vec4<f32>> = Vec::new();
+        if self.points.is_empty() {
+            return points;
+        }
+        if self.points.iter().any(|p| !(p.w > 0.0 && p.w.is_finite())) {
+            return points;
+        }
+        let lvalues = self.cumulative_lengths_xyz();
+        for d in spaced_distances(lvalues[lvalues.len()-1], n) {
+            if self.points.len() < 2 {
+                points.push(self.points[0]);
+                continue;
+            }
+            let (index, t) = find_segment(&lvalues, d);
+            let p1 = self.points[index];
+            let p2 = self.points[index+1];
+            // blend in homogeneous coordinates
+            let h1 = Vec4::new(p1.x * p1.w, p1.y * p1.w, p1.z * p1.w, p1.w);
+            let h2 = Vec4::new(p2.x * p2.w, p2.y * p2.w, p2.z * p2.w, p2.w);
+            let h = h1 * (1.0 - t) + h2 * t;
+            points.push(Vec4::new(h.x / h.w, h.y / h.w, h.z / h.w, h.w));
+        }
+        points
+    }
+    /// Interpolate one point for each normalized parameter in `ts`
+    /// (0 is the start, 1 the end of the polyline). Parameters outside
+    /// of that range are clamped.
+    pub fn evaluate_at_params(&self, ts: &[f32]) -> Vec<Vec4<f32>>
+    {
+        let mut points: Vec<Vec4<f32>> = Vec::with_capacity(ts.len());
+        if self.points.is_empty() {
+            return points;
+        }
+        let lvalues = self.cumulative_lengths();
+        let length = lvalues[lvalues.len()-1];
+        for t in ts {
+            points.push(self.interpolate(&lvalues, t.clamp(0.0, 1.0) * length));
+        }
+        points
+    }
+    /// Distribute `n` points evenly between the normalized parameters
+    /// `t0` and `t1` (both included and clamped to the polyline). If
+    /// `t1` is smaller than `t0` the points are in reverse order.
+    pub fn evaluate_range(&self, t0: f32, t1: f32, n: usize) -> Vec<Vec4<f32>>
+    {
+        let mut points: Vec<Vec4<f32>> = Vec::with_capacity(n);
+        if self.points.is_empty() {
+            return points;
+        }
+        let lvalues = self.cumulative_lengths();
+        let length = lvalues[lvalues.len()-1];
+        let d0 = t0.clamp(0.0, 1.0) * length;
+        let d1 = t1.clamp(0.0, 1.0) * length;
+        for d in spaced_distances(d1 - d0, n) {
+            points.push(self.interpolate(&lvalues, d0 + d));
+        }
+        points
+    }
+    /// Distances along the polyline at which `evaluate` would place
+    /// `n` points (from 0 to the length of the polyline).
+    pub fn sample_distances(&self, n: usize) -> Vec<f32>
+    {
+        let lvalues = self.cumulative_lengths();
+        spaced_distances(lvalues[lvalues.len()-1], n)
+    }
+    /// Interpolate `scalars` (one value per point) linearly along the
+    /// polyline for `n` evenly spaced samples (matching the points of
+    /// `evaluate`).
+    pub fn sample_scalar(&self, scalars: &[f32], n: usize)
+                         -> Result<Vec<f32>, PathError>
+    {
+        if scalars.len() != self.points.len() {
+            return Err(PathError::LengthMismatch { expected: self.points.len(),
+                                                   found: scalars.len() });
+        }
+        let mut values: Vec<f32> = Vec::with_capacity(n);
+        if self.points.len() < 2 {
+            if let Some(&value) = scalars.first() {
+                values.resize(n, value);
+            }
+            return Ok(values);
+        }
+        let lvalues = self.cumulative_lengths();
+        for d in spaced_distances(lvalues[lvalues.len()-1], n) {
+            let (index, t) = find_segment(&lvalues, d);
+            values.push(scalars[index] * (1.0 - t) + scalars[index+1] * t);
+        }
+        Ok(values)
+    }
+    /// Distribute `n` points evenly in time (instead of arc length),
+    /// given the (non-decreasing) time `old_times` of each point, and
+    /// interpolate their positions.
+    pub fn retime(&self, old_times: &[f32], n: usize)
+                  -> Result<Vec<Vec4<f32>>, PathError>
+    {
+        if old_times.len() != self.points.len() {
+            return Err(PathError::LengthMismatch { expected: self.points.len(),
+                                                   found: old_times.len() });
+        }
+        let mut points: Vec<Vec4<f32>> = Vec::with_capacity(n);
+        if self.points.len() < 2 {
+            if let Some(&point) = self.points.first() {
+                points.resize(n, point);
+            }
+            return Ok(points);
+        }
+        let start = old_times[0];
+        let duration = old_times[old_times.len()-1] - start;
+        for offset in spaced_distances(duration, n) {
+            let (index, t) = find_segment(old_times, start + offset);
+            points.push(self.points[index] * (1.0 - t) + self.points[index+1] * t);
+        }
+        Ok(points)
+    }
+    /// Distribute `n` points along a polyline (like `evaluate`), each
+    /// keyed by its distance from the start (e.g. for a lookup table).
+    pub fn to_param_table(&self, n: usize) -> Vec<(f32, Vec4<f32>)>
+    {
+        let mut table: Vec<(f32, Vec4<f32>)> = Vec::with_capacity(n);
+        if self.points.is_empty() {
+            return table;
+        }
+        let lvalues = self.cumulative_lengths();
+        for d in spaced_distances(lvalues[lvalues.len()-1], n) {
+            table.push((d, self.interpolate(&lvalues, d)));
+        }
+        table
+    }
+    /// Distribute points along a polyline with a spacing of `base` on
+    /// straight parts, which gets denser where the polyline bends: the
+    /// spacing is divided by `1 + curvature_scale * curvature` using the
+    /// curvature of the points interpolated along the polyline. Both
+    /// end points are always included.
+    pub fn curvature_density(&self, base: f32, curvature_scale: f32) ->
+        Vec<Vec4<f32>>
+    {
+        let mut points: Vec<Vec4<f32>> = Vec::new();
+        if self.points.is_empty() || base <= 0.0 {
+            return points;
+        }
+        let lvalues = self.cumulative_lengths();
+        let length = lvalues[lvalues.len()-1];
+        let mut curvatures: Vec<f32> = Vec::with_capacity(self.points.len());
+        for i in 0..self.points.len() {
+            curvatures.push(self.curvature_at_vertex(i));
+        }
+        let mut d = 0.0;
+        while d < length {
+            points.push(self.interpolate(&lvalues, d));
+            let (index, t) = find_segment(&lvalues, d);
+            let curvature = curvatures[index] * (1.0 - t) +
+                curvatures[index+1] * t;
+            d += base / (1.0 + curvature_scale * curvature).max(1.0);
+        }
+        points.push(self.points[self.points.len()-1]);
+        points
+    }
+    /// Lateral (centripetal) acceleration `v^2 * curvature` when
+    /// travelling at constant speed `v`, at `n` evenly spaced samples.
+    /// The (Menger) curvature of the points is interpolated linearly
+    /// along each segment.
+    pub fn lateral_acceleration(&self, v: f32, n: usize) -> Vec<f32>
+    {
+        if self.points.len() < 2 {
+            return vec![0.0; n];
+        }
+        let lvalues = self.cumulative_lengths();
+        let mut accelerations: Vec<f32> = Vec::with_capacity(n);
+        for d in spaced_distances(lvalues[lvalues.len()-1], n) {
+            let (index, t) = find_segment(&lvalues, d);
+            let curvature = self.curvature_at_vertex(index) * (1.0 - t) +
+                self.curvature_at_vertex(index+1) * t;
+            accelerations.push(v * v * curvature);
+        }
+        accelerations
+    }
+    /// Distribute `n` points along a polyline (like `evaluate`) and
+    /// pair each of them with the (normalized) tangent direction of
+    /// the segment it was found on.
+    pub fn frames(&self, n: usize) -> Vec<(Vec4<f32>, Vec4<f32>)>
+    {
+        let mut frames: Vec<(Vec4<f32>, Vec4<f32>)> = Vec::new();
+        if self.points.is_empty() {
+            return frames;
+        }
+        let lvalues = self.cumulative_lengths();
+        for d in spaced_distances(lvalues[lvalues.len()-1], n) {
+            frames.push(self.frame(&lvalues, d));
+        }
+        frames
+    }
+    /// First and second derivative (with respect to the normalized
+    /// parameter between 0 and 1) at `n` evenly spaced samples,
+    /// estimated by finite differences. Central differences are used in
+    /// between, one-sided ones at both end points (of second order if
+    /// there are at least four samples).
+    pub fn derivatives(&self, n: usize) -> Vec<(Vec4<f32>, Vec4<f32>)>
+    {
+        let mut derivatives: Vec<(Vec4<f32>, Vec4<f32>)> =
+            Vec::with_capacity(n);
+        if self.points.is_empty() {
+            return derivatives;
+        }
+        let samples = self.sample(n);
+        if n < 2 {
+            for _i in 0..n {
+                derivatives.push((na::zero(), na::zero()));
+            }
+            return derivatives;
+        }
+        let h = 1.0 / ((n-1) as f32);
+        for i in 0..n {
+            let first = if i == 0 {
+                (samples[1] - samples[0]) / h
+            } else if i == n - 1 {
+                (samples[i] - samples[i-1]) / h
+            } else {
+                (samples[i+1] - samples[i-1]) / (2.0 * h)
+            };
+            let mut second: Vec4<f32> = na::zero();
+            if n > 3 && (i == 0 || i == n - 1) {
+                // forward (or backward) difference from the end point
+                let (s0, s1, s2, s3) = if i == 0 {
+                    (samples[0], samples[1], samples[2], samples[3])
+                } else {
+                    (samples[i], samples[i-1], samples[i-2], samples[i-3])
+                };
+                second = (s0 * 2.0 - s1 * 5.0 + s2 * 4.0 - s3) / (h * h);
+            } else if n > 2 {
+                // central difference around the nearest interior sample
+                let j = i.clamp(1, n - 2);
+                second = (samples[j+1] - samples[j] * 2.0 + samples[j-1]) /
+                    (h * h);
+            }
+            derivatives.push((first, second));
+        }
+        derivatives
+    }
+    /// Distribute `n` points along a polyline (like `evaluate`) and
+    /// offset each of them by `half_gauge` to the left and to the right
+    /// (perpendicular to the tangent within the XY plane). Returns the
+    /// left and the right rail.
+    pub fn rails_2d(&self, half_gauge: f32, n: usize)
+                    -> (Vec<Vec4<f32>>, Vec<Vec4<f32>>)
+    {
+        let mut left: Vec<Vec4<f32>> = Vec::with_capacity(n);
+        let mut right: Vec<Vec4<f32>> = Vec::with_capacity(n);
+        for (point, tangent) in self.frames(n) {
+            let norm = (tangent.x * tangent.x + tangent.y * tangent.y).sqrt();
+            let mut offset: Vec4<f32> = na::zero();
+            if norm > 0.0 {
+                offset.x = -tangent.y / norm * half_gauge;
+                offset.y = tangent.x / norm * half_gauge;
+            }
+            left.push(point + offset);
+            right.push(point - offset);
+        }
+        (left, right)
+    }
+    /// Left and right boundary (`n` evenly spaced samples each) of a
+    /// ribbon of total `width` centered on the polyline within the XY
+    /// plane. Corners are mitered (with the miter limited to four times
+    /// half the width), so the boundaries stay parallel to the segments.
+    pub fn ribbon_2d(&self, width: f32, n: usize)
+                     -> (Vec<Vec4<f32>>, Vec<Vec4<f32>>)
+    {
+        let mut left: Vec<Vec4<f32>> = Vec::with_capacity(n);
+        let mut right: Vec<Vec4<f32>> = Vec::with_capacity(n);
+        let m = self.points.len();
+        if m < 2 {
+            if let Some(&point) = self.points.first() {
+                left.resize(n, point);
+                right.resize(n, point);
+            }
+            return (left, right);
+        }
+        // left normal of each segment
+        let mut normals: Vec<(f32, f32)> = Vec::with_capacity(m - 1);
+        for i in 0..(m - 1) {
+            let direction = self.segment_direction(i);
+            let norm = (direction.x * direction.x +
+                        direction.y * direction.y).sqrt();
+            if norm > 0.0 {
+                normals.push((-direction.y / norm, direction.x / norm));
+            } else {
+                normals.push((0.0, 0.0));
+            }
+        }
+        // miter offset at each point
+        let half = 0.5 * width;
+        let mut miters: Vec<Vec4<f32>> = Vec::with_capacity(m);
+        for i in 0..m {
+            let n0 = normals[i.max(1) - 1];
+            let n1 = normals[i.min(m - 2)];
+            let (mx, my) = (n0.0 + n1.0, n0.1 + n1.1);
+            let norm = (mx * mx + my * my).sqrt();
+            let mut miter: Vec4<f32> = na::zero();
+            if norm > 0.0 {
+                let (mx, my) = (mx / norm, my / norm);
+                let cos = (mx * n1.0 + my * n1.1).max(0.25);
+                miter.x = mx * half / cos;
+                miter.y = my * half / cos;
+            }
+            miters.push(miter);
+        }
+        let lvalues = self.cumulative_lengths();
+        for d in spaced_distances(lvalues[m-1], n) {
+            let (index, t) = find_segment(&lvalues, d);
+            let point = self.points[index] * (1.0 - t) + self.points[index+1] * t;
+            let offset = miters[index] * (1.0 - t) + miters[index+1] * t;
+            left.push(point + offset);
+            right.push(point - offset);
+        }
+        (left, right)
+    }
+    /// Tangent angle (within the XY plane, against the x axis) at `n`
+    /// evenly spaced samples, each paired with its distance from the
+    /// start. The angles are unwrapped, so they change continuously
+    /// instead of jumping by 2 pi.
+    pub fn tangent_angle_profile(&self, n: usize) -> Vec<(f32, f32)>
+    {
+        let mut profile: Vec<(f32, f32)> = Vec::with_capacity(n);
+        if self.points.is_empty() {
+            return profile;
+        }
+        let lvalues = self.cumulative_lengths();
+        let mut previous: Option<f32> = None;
+        for d in spaced_distances(lvalues[lvalues.len()-1], n) {
+            let (_point, tangent) = self.frame(&lvalues, d);
+            let mut angle = tangent.y.atan2(tangent.x);
+            if let Some(previous) = previous {
+                let pi = std::f32::consts::PI;
+                while angle - previous > pi {
+                    angle -= 2.0 * pi;
+                }
+                while angle - previous < -pi {
+                    angle += 2.0 * pi;
+                }
+            }
+            previous = Some(angle);
+            profile.push((d, angle));
+        }
+        profile
+    }
+    /// Interpolate the point at distance `d` along the polyline (clamped
+    /// to both ends) together with the (normalized) tangent direction
+    /// (or `None` for an empty path).
+    pub fn frame_at_distance(&self, d: f32) -> Option<(Vec4<f32>, Vec4<f32>)>
+    {
+        if self.points.is_empty() {
+            return None;
+        }
+        Some(self.frame(&self.cumulative_lengths(), d))
+    }
+    /// Distribute points `spacing` apart along a polyline (starting at
+    /// the first point), but never more than `max_count` of them. If
+    /// the limit would be exceeded the spacing is increased so that
+    /// `max_count` points cover the whole polyline.
+    pub fn resample_by_spacing_capped(&self, spacing: f32, max_count: usize)
+                                      -> Vec<Vec4<f32>>
+    {
+        let mut points: Vec<Vec4<f32>> = Vec::new();
+        if self.points.is_empty() || max_count == 0 {
+            return points;
+        }
+        let lvalues = self.cumulative_lengths();
+        let length = lvalues[lvalues.len()-1];
+        let mut count = max_count;
+        let mut step = 0.0;
+        if spacing > 0.0 && ((length / spacing).floor() as usize) < max_count {
+            count = (length / spacing).floor() as usize + 1;
+            step = spacing;
+        } else if max_count > 1 {
+            step = length / ((max_count-1) as f32);
+        }
+        for i in 0..count {
+            points.push(self.interpolate(&lvalues, step * (i as f32)));
+        }
+        points
+    }
+    /// Distribute points `spacing` apart along a polyline (starting at
+    /// the first point), but move each of them along the polyline by a
+    /// pseudo-random distance of up to `jitter` in either direction
+    /// (staying within the polyline). The same `seed` always gives the
+    /// same points.
+    pub fn resample_jittered(&self, spacing: f32, jitter: f32, seed: u64)
+                             -> Vec<Vec4<f32>>
+    {
+        let mut points: Vec<Vec4<f32>> = Vec::new();
+        if self.points.is_empty() {
+            return points;
+        }
+        let lvalues = self.cumulative_lengths();
+        let length = lvalues[lvalues.len()-1];
+        let mut count = 1;
+        if spacing > 0.0 {
+            count = (length / spacing).floor() as usize + 1;
+        }
+        let mut state = seed;
+        for i in 0..count {
+            // uniform in [0, 1) from the upper 24 bits
+            let r = (splitmix64(&mut state) >> 40) as f32 / ((1 << 24) as f32);
+            let d = spacing.max(0.0) * (i as f32) + (2.0 * r - 1.0) * jitter;
+            points.push(self.interpolate(&lvalues, d.clamp(0.0, length)));
+        }
+        points
+    }
+    /// Distribute points along a polyline with (at least)
+    /// `points_per_unit` points per unit length, so paths of different
+    /// length get sampled with the same density. A non-finite density
+    /// gives no points at all.
+    pub fn resample_by_density(&self, points_per_unit: f32) -> Vec<Vec4<f32>>
+    {
+        if self.points.is_empty() || !points_per_unit.is_finite() {
+            return Vec::new();
+        }
+        let length = self.length_streaming();
+        let count = (length * points_per_unit.max(0.0)).ceil() as usize;
+        self.sample(count.saturating_add(1))
+    }
+    /// Resample the polyline with evenly spaced points (including both
+    /// end points) which are as far apart as possible, but not more
+    /// than `max_spacing` and not less than `min_spacing`. If both
+    /// can't be met at once, the minimum spacing wins.
+    pub fn resample_clamped_spacing(&self, min_spacing: f32, max_spacing: f32)
+                                    -> Path
+    {
+        if self.points.is_empty() {
+            return Path::from_points(Vec::new());
+        }
+        let length = self.length_streaming();
+        let mut count = 1;
+        if max_spacing > 0.0 {
+            count = ((length / max_spacing).ceil() as usize).max(1);
+        }
+        if min_spacing > 0.0 && length / (count as f32) < min_spacing {
+            count = ((length / min_spacing).floor() as usize).max(1);
+        }
+        Path::from_points(self.sample(count + 1))
+    }
+    /// Distance (within the XY plane) from `q` to the closest point of
+    /// the polyline. The sign tells on which side of the closest
+    /// segment `q` lies: positive on the left, negative on the right.
+    pub fn signed_distance_2d(&self, q: Vec4<f32>) -> f32
+    {
+        let mut distance = f32::MAX;
+        if self.points.len() == 1 {
+            let dx = q.x - self.points[0].x;
+            let dy = q.y - self.points[0].y;
+            distance = (dx * dx + dy * dy).sqrt();
+        }
+        for i in 1..self.points.len() {
+            let a = self.points[i-1];
+            let b = self.points[i];
+            let t = project_2d(a, b, q);
+            let dx = q.x - (a.x + (b.x - a.x) * t);
+            let dy = q.y - (a.y + (b.y - a.y) * t);
+            let dist = (dx * dx + dy * dy).sqrt();
+            if dist < distance.abs() {
+                let cross = (b.x - a.x) * (q.y - a.y) - (b.y - a.y) * (q.x - a.x);
+                distance = if cross < 0.0 { -dist } else { dist };
+            }
+        }
+        distance
+    }
+    /// Distribute `n` points along a polyline whose `w` components
+    /// store a radius per point (e.g. to generate a tube). Each point
+    /// (with `w` reset to 1) is paired with the interpolated radius.
+    pub fn to_tube_radii(&self, n: usize) -> Vec<(Vec4<f32>, f32)>
+    {
+        let mut samples: Vec<(Vec4<f32>, f32)> = Vec::with_capacity(n);
+        if self.points.is_empty() {
+            return samples;
+        }
+        // space the samples by the xyz lengths, not by the radii
+        let lvalues = self.cumulative_lengths_xyz();
+        for d in spaced_distances(lvalues[lvalues.len()-1], n) {
+            let point = self.interpolate(&lvalues, d);
+            samples.push((Vec4::new(point.x, point.y, point.z, 1.0), point.w));
+        }
+        samples
+    }
+    /// Find the point of the polyline which is closest to `q` (or
+    /// `None` for an empty path).
+    pub fn closest_point(&self, q: Vec4<f32>) -> Option<Vec4<f32>>
+    {
+        if self.points.is_empty() {
+            return None;
+        }
+        Some(self.project(q).2)
+    }
+    /// Normalized parameter (0 at the start, 1 at the end) of the point
+    /// of the polyline which is closest to `q`.
+    pub fn closest_param(&self, q: Vec4<f32>) -> f32
+    {
+        let length = self.length_streaming();
+        if length <= 0.0 {
+            return 0.0;
+        }
+        self.progress_distance(q) / length
+    }
+    /// Distance along the polyline from the start to the point which is
+    /// closest to `q` (e.g. how far a position fix has progressed).
+    pub fn progress_distance(&self, q: Vec4<f32>) -> f32
+    {
+        if self.points.len() < 2 {
+            return 0.0;
+        }
+        let lvalues = self.cumulative_lengths();
+        let (index, t, _point) = self.project(q);
+        lvalues[index] * (1.0 - t) + lvalues[index+1] * t
+    }
+    /// Winding number (within the XY plane) of the polyline around `q`,
+    /// treating it as closed (the closing segment from the last point
+    /// back to the first one is implied). Positive for counter-clockwise
+    /// loops.
+    pub fn winding_number_2d(&self, q: Vec4<f32>) -> i32
+    {
+        let m = self.points.len();
+        let mut winding = 0;
+        for i in 0..m {
+            let a = self.points[i];
+            let b = self.points[(i+1) % m];
+            if a.y <= q.y {
+                // upward crossing with q on the left
+                if b.y > q.y && cross_2d(a, b, q) > 0.0 {
+                    winding += 1;
+                }
+            } else if b.y <= q.y && cross_2d(a, b, q) < 0.0 {
+                // downward crossing with q on the right
+                winding -= 1;
+            }
+        }
+        winding
+    }
+    /// Points (in order) where the polyline crosses the infinite line
+    /// through `a` and `b` within the XY plane. Each segment crosses at
+    /// most once, a point exactly on the line counts as lying on its
+    /// right side. A point which only touches the line (with both
+    /// neighbours on the same side) is not a crossing.
+    pub fn intersect_line_2d(&self, a: Vec4<f32>, b: Vec4<f32>) -> Vec<Vec4<f32>>
+    {
+        let mut crossings: Vec<Vec4<f32>> = Vec::new();
+        // did the previous segment cross exactly at its end point?
+        let mut at_end = false;
+        for i in 1..self.points.len() {
+            let p = self.points[i-1];
+            let q = self.points[i];
+            let s0 = cross_2d(a, b, p);
+            let s1 = cross_2d(a, b, q);
+            if (s0 > 0.0) != (s1 > 0.0) {
+                if at_end && s0 == 0.0 {
+                    // touched from the left side and back again
+                    crossings.pop();
+                    at_end = false;
+                    continue;
+                }
+                let t = s0 / (s0 - s1);
+                crossings.push(p * (1.0 - t) + q * t);
+                at_end = s1 == 0.0;
+            } else {
+                at_end = false;
+            }
+        }
+        crossings
+    }
+    /// Length of the parts of the polyline which lie inside the
+    /// axis-aligned box spanned by `min` and `max` (w is ignored),
+    /// clipping segments at the sides of the box.
+    pub fn length_in_aabb(&self, min: Vec4<f32>, max: Vec4<f32>) -> f32
+    {
+        let mut length = 0.0;
+        for i in 1..self.points.len() {
+            let a = self.points[i-1];
+            let b = self.points[i];
+            if let Some((t0, t1)) = clip_segment(a, b, min, max) {
+                let vector: Vec4<f32> = b - a;
+                length += na::norm(&vector) * (t1 - t0);
+            }
+        }
+        length
+    }
+    /// Distance from the polyline to the axis-aligned box spanned by
+    /// `min` and `max` (w is ignored), 0 if the polyline enters the box.
+    pub fn distance_to_aabb(&self, min: Vec4<f32>, max: Vec4<f32>) -> f32
+    {
+        if self.points.is_empty() {
+            return f32::MAX;
+        }
+        let mut distance = aabb_distance(self.points[0], min, max);
+        for i in 1..self.points.len() {
+            let a = self.points[i-1];
+            let b = self.points[i];
+            if clip_segment(a, b, min, max).is_some() {
+                return 0.0;
+            }
+            // the distance is convex along the segment, so a ternary
+            // search finds its minimum
+            let mut low = 0.0f32;
+            let mut high = 1.0f32;
+            for _i in 0..40 {
+                let t0 = low + (high - low) / 3.0;
+                let t1 = high - (high - low) / 3.0;
+                if aabb_distance(a * (1.0 - t0) + b * t0, min, max) <
+                    aabb_distance(a * (1.0 - t1) + b * t1, min, max) {
+                    high = t1;
+                } else {
+                    low = t0;
+                }
+            }
+            let t = 0.5 * (low + high);
+            distance = distance.min(aabb_distance(a * (1.0 - t) + b * t, min, max))
+                .min(aabb_distance(b, min, max));
+        }
+        distance
+    }
+    /// Indices and positions (in order) of all original points within
+    /// `radius` of `center`.
+    pub fn vertices_within(&self, center: Vec4<f32>, radius: f32)
+                           -> Vec<(usize, Vec4<f32>)>
+    {
+        let mut vertices: Vec<(usize, Vec4<f32>)> = Vec::new();
+        for (i, point) in self.points.iter().enumerate() {
+            let vector: Vec4<f32> = *point - center;
+            if na::norm(&vector) <= radius {
+                vertices.push((i, *point));
+            }
+        }
+        vertices
+    }
+    /// Pairs of non-adjacent points (as indices, in order) which lie
+    /// within `epsilon` of each other, usually a hint that the polyline
+    /// touches itself.
+    pub fn find_near_coincidences(&self, epsilon: f32) -> Vec<(usize, usize)>
+    {
+        let mut pairs: Vec<(usize, usize)> = Vec::new();
+        for i in 0..self.points.len() {
+            for j in (i+2)..self.points.len() {
+                let vector: Vec4<f32> = self.points[j] - self.points[i];
+                if na::norm(&vector) <= epsilon {
+                    pairs.push((i, j));
+                }
+            }
+        }
+        pairs
+    }
+    /// Length of the parts of the polyline (within the XY plane) which
+    /// retrace an earlier part: they run (roughly parallel, within 25
+    /// degrees) within `epsilon` of a part at least `2 * epsilon`
+    /// earlier along the polyline. Only the later of both parts counts.
+    pub fn overlap_length_2d(&self, epsilon: f32) -> f32
+    {
+        let m = self.points.len();
+        if m < 2 || epsilon <= 0.0 {
+            return 0.0;
+        }
+        // chord lengths and directions within the XY plane
+        let mut lvalues: Vec<f32> = Vec::with_capacity(m);
+        let mut directions: Vec<(f32, f32)> = Vec::with_capacity(m - 1);
+        lvalues.push(0.0);
+        for i in 1..m {
+            let (a, b) = (self.points[i-1], self.points[i]);
+            let length = distance_2d(a, b);
+            lvalues.push(lvalues[i-1] + length);
+            if length > 0.0 {
+                directions.push(((b.x - a.x) / length, (b.y - a.y) / length));
+            } else {
+                directions.push((0.0, 0.0));
+            }
+        }
+        let mut overlap = 0.0;
+        for j in 0..(m - 1) {
+            let (a, b) = (self.points[j], self.points[j+1]);
+            let segment_length = lvalues[j+1] - lvalues[j];
+            // check pieces of the segment not longer than epsilon
+            let pieces = ((segment_length / epsilon).ceil() as usize).max(1);
+            for piece in 0..pieces {
+                let t = (piece as f32 + 0.5) / (pieces as f32);
+                let q = a * (1.0 - t) + b * t;
+                let d = lvalues[j] + t * segment_length;
+                let retraced = (0..(j + 1)).any(|i| {
+                    let (c, e) = (self.points[i], self.points[i+1]);
+                    let s = project_2d(c, e, q);
+                    let dot = directions[i].0 * directions[j].0 +
+                        directions[i].1 * directions[j].1;
+                    dot.abs() >= 0.9 &&
+                        d - (lvalues[i] * (1.0 - s) + lvalues[i+1] * s) >
+                        2.0 * epsilon &&
+                        distance_2d(q, c * (1.0 - s) + e * s) <= epsilon
+                });
+                if retraced {
+                    overlap += segment_length / (pieces as f32);
+                }
+            }
+        }
+        overlap
+    }
+    /// Fraction of the length of the polyline which lies within
+    /// `radius` of `center`.
+    pub fn fraction_within(&self, center: Vec4<f32>, radius: f32) -> f32
+    {
+        let mut covered = 0.0;
+        let mut length = 0.0;
+        for i in 1..self.points.len() {
+            let direction: Vec4<f32> = self.points[i] - self.points[i-1];
+            let offset: Vec4<f32> = self.points[i-1] - center;
+            let segment_length = na::norm(&direction);
+            length += segment_length;
+            // solve |offset + t * direction| = radius for t
+            let a = na::sqnorm(&direction);
+            let b = 2.0 * na::dot(&offset, &direction);
+            let c = na::sqnorm(&offset) - radius * radius;
+            let discriminant = b * b - 4.0 * a * c;
+            if a <= 0.0 || discriminant <= 0.0 {
+                continue;
+            }
+            let root = discriminant.sqrt();
+            let t0 = ((-b - root) / (2.0 * a)).max(0.0);
+            let t1 = ((-b + root) / (2.0 * a)).min(1.0);
+            if t1 > t0 {
+                covered += (t1 - t0) * segment_length;
+            }
+        }
+        if length > 0.0 {
+            covered / length
+        } else {
+            0.0
+        }
+    }
+    /// Maximum distance from any point of this polyline to the closest
+    /// point of `other` (e.g. to measure how much a simplified path
+    /// deviates from the original one). An empty `other` is infinitely
+    /// far away (`f32::MAX`, unless this path is empty as well).
+    pub fn max_deviation_from(&self, other: &Path) -> f32
+    {
+        if other.points.is_empty() {
+            return if self.points.is_empty() { 0.0 } else { f32::MAX };
+        }
+        let mut deviation = 0.0;
+        for point in &self.points {
+            let vector: Vec4<f32> = *point - other.project(*point).2;
+            deviation = na::norm(&vector).max(deviation);
+        }
+        deviation
+    }
+    /// Project `q` onto the (non-empty) polyline. Returns the index of
+    /// the closest segment, the local parameter within it, and the
+    /// projected point.
+    fn project(&self, q: Vec4<f32>) -> (usize, f32, Vec4<f32>)
+    {
+        if self.points.len() < 2 {
+            return (0, 0.0, self.points[0]);
+        }
+        let mut best = (0, 0.0, self.points[0]);
+        let mut best_dist = f32::MAX;
+        for i in 1..self.points.len() {
+            let a = self.points[i-1];
+            let b = self.points[i];
+            let t = project_segment(a, b, q);
+            let point = a * (1.0 - t) + b * t;
+            let dist = na::sqnorm(&(q - point));
+            if dist < best_dist {
+                best = (i-1, t, point);
+                best_dist = dist;
+            }
+        }
+        best
+    }
+    /// Distribute `n` points along a closed (uniform) Catmull-Rom spline
+    /// through all points, where the last point connects back to the
+    /// first one. The points are evenly spaced in the spline parameter
+    /// and the last one coincides with the first one.
+    pub fn evaluate_catmull_rom_closed(&self, n: usize) -> Vec<Vec4<f32>>
+    {
+        let mut points: Vec<Vec4<f32>> = Vec::with_capacity(n);
+        let m = self.points.len();
+        if m == 0 {
+            return points;
+        }
+        for u in spaced_distances(m as f32, n) {
+            let j = (u.floor() as usize).min(m-1);
+            let t = u - (j as f32);
+            points.push(catmull_rom(self.points[(j+m-1) % m],
+                                    self.points[j],
+                                    self.points[(j+1) % m],
+                                    self.points[(j+2) % m],
+                                    t));
+        }
+        points
+    }
+    /// Distribute `n` points (with uniform parameter steps) along the
+    /// closed uniform B-spline of the given `degree` which uses the
+    /// points of the polyline as (periodic) control points. Unlike
+    /// Catmull-Rom splines it doesn't pass through the points, but is
+    /// smoother. The last point repeats the first one.
+    pub fn evaluate_bspline_closed(&self, degree: usize, n: usize)
+                                   -> Vec<Vec4<f32>>
+    {
+        let mut points: Vec<Vec4<f32>> = Vec::with_capacity(n);
+        let m = self.points.len();
+        if m == 0 {
+            return points;
+        }
+        let mut control: Vec<Vec4<f32>> = vec![na::zero(); degree + 1];
+        for u in spaced_distances(m as f32, n) {
+            let k = (u.floor() as usize).min(m - 1);
+            // de Boor's algorithm with integer knots, shifted so that the
+            // curve at parameter k is centered around points[k]
+            let first = k + (degree / m + 1) * m + degree.div_ceil(2) - degree;
+            for (i, c) in control.iter_mut().enumerate() {
+                *c = self.points[(first + i) % m];
+            }
+            for r in 1..(degree + 1) {
+                for i in (r..(degree + 1)).rev() {
+                    let knot = (k + i) as f32 - degree as f32;
+                    let alpha = (u - knot) / ((degree + 1 - r) as f32);
+                    control[i] = control[i-1] * (1.0 - alpha) + control[i] * alpha;
+                }
+            }
+            points.push(control[degree]);
+        }
+        points
+    }
+    /// Approximate the arc length of the (open) Catmull-Rom spline
+    /// through all points by summing up the chords of `subdivisions`
+    /// pieces per segment. The end points are repeated to get tangents
+    /// at both ends.
+    pub fn catmull_rom_length(&self, subdivisions: usize) -> f32
+    {
+        let m = self.points.len();
+        let subdivisions = subdivisions.max(1);
+        let mut length = 0.0;
+        for j in 1..m {
+            let (p0, p1, p2, p3) = self.catmull_rom_segment(j-1);
+            let mut previous = p1;
+            for k in 1..(subdivisions+1) {
+                let t = (k as f32) / (subdivisions as f32);
+                let point = catmull_rom(p0, p1, p2, p3, t);
+                let vector: Vec4<f32> = point - previous;
+                length += na::norm(&vector);
+                previous = point;
+            }
+        }
+        length
+    }
+    /// Speed (the length of the derivative) of the open Catmull-Rom
+    /// spline through all points at `n` evenly spaced parameters (each
+    /// segment covers a parameter range of 1). Because the end points
+    /// are repeated, the speed drops towards both ends.
+    pub fn catmull_rom_speed(&self, n: usize) -> Vec<f32>
+    {
+        let mut speeds: Vec<f32> = Vec::with_capacity(n);
+        let m = self.points.len();
+        if m < 2 {
+            for _i in 0..n {
+                speeds.push(0.0);
+            }
+            return speeds;
+        }
+        for u in spaced_distances((m-1) as f32, n) {
+            let j = (u.floor() as usize).min(m-2);
+            let (p0, p1, p2, p3) = self.catmull_rom_segment(j);
+            let derivative = catmull_rom_derivative(p0, p1, p2, p3,
+                                                    u - (j as f32));
+            speeds.push(na::norm(&derivative));
+        }
+        speeds
+    }
+    /// Distribute `n` points along the polyline (like `evaluate`), but
+    /// keep all original points in between as well. Chords between
+    /// evenly spaced points cut corners, keeping the original corners
+    /// guarantees the resulting path has the same length (the result
+    /// has therefore at least `n` points).
+    pub fn resample_length_preserving(&self, n: usize) -> Path
+    {
+        if self.points.is_empty() {
+            return Path::from_points(Vec::new());
+        }
+        let lvalues = self.cumulative_lengths();
+        let mut distances = spaced_distances(lvalues[lvalues.len()-1], n);
+        for l in &lvalues {
+            distances.push(*l);
+        }
+        // a total order doesn't panic on NaNs (from non-finite points)
+        distances.sort_by(|a, b| a.total_cmp(b));
+        distances.dedup();
+        let mut points: Vec<Vec4<f32>> = Vec::with_capacity(distances.len());
+        for d in distances {
+            points.push(self.interpolate(&lvalues, d));
+        }
+        Path::from_points(points)
+    }
+    /// Move the points along the polyline so that they are equally
+    /// spaced (in terms of arc length), keeping the number of points.
+    pub fn equalize_spacing(&self) -> Path
+    {
+        if self.points.is_empty() {
+            return Path::from_points(Vec::new());
+        }
+        Path::from_points(self.sample(self.points.len()))
+    }
+    /// Smooth the polyline by applying Chaikin's corner cutting
+    /// `iterations` times: every segment is replaced by the points at a
+    /// quarter and three quarters of it. Both end points are kept.
+    pub fn chaikin(&self, iterations: usize) -> Path
+    {
+        let mut points = self.points.clone();
+        for _i in 0..iterations {
+            if points.len() < 3 {
+                break;
+            }
+            let mut cut: Vec<Vec4<f32>> = Vec::with_capacity(2 * points.len());
+            cut.push(points[0]);
+            for pair in points.windows(2) {
+                cut.push(pair[0] * 0.75 + pair[1] * 0.25);
+                cut.push(pair[0] * 0.25 + pair[1] * 0.75);
+            }
+            cut.push(points[points.len()-1]);
+            points = cut;
+        }
+        Path::from_points(points)
+    }
+    /// Drop points which are closer than `min_len` to the previously
+    /// kept point (merging short segments into the previous one). Both
+    /// end points are kept, if the last segment is too short the kept
+    /// points before the last one are dropped instead (until the last
+    /// segment is long enough or only the first point is left).
+    pub fn collapse_short_segments(&self, min_len: f32) -> Path
+    {
+        let mut points: Vec<Vec4<f32>> = Vec::new();
+        for (i, point) in self.points.iter().enumerate() {
+            if let Some(&previous) = points.last() {
+                let vector: Vec4<f32> = *point - previous;
+                if na::norm(&vector) < min_len && i + 1 < self.points.len() {
+                    continue;
+                }
+            }
+            if i > 0 && i + 1 == self.points.len() {
+                // merge the last kept points into the end point
+                while points.len() > 1 {
+                    let vector: Vec4<f32> = *point - points[points.len()-1];
+                    if na::norm(&vector) >= min_len {
+                        break;
+                    }
+                    points.pop();
+                }
+            }
+            points.push(*point);
+        }
+        let mut path = Path::from_points(points);
+        path.original_point_count = self.original_point_count;
+        path
+    }
+    /// Simplify the polyline (using Douglas-Peucker) to at most `target`
+    /// points, searching for the smallest tolerance which achieves
+    /// that. Both end points are always kept (even for a `target` below
+    /// 2).
+    pub fn simplify_to_count(&self, target: usize) -> Path
+    {
+        let mut keep: Vec<bool> = vec![true; self.points.len()];
+        if self.points.len() > target.max(2) {
+            let (min, max) = self.aabb();
+            // no point is farther away from any chord than this
+            let mut high = na::norm(&(max - min));
+            let mut low = 0.0;
+            keep = self.douglas_peucker(high);
+            for _i in 0..32 {
+                let epsilon = 0.5 * (low + high);
+                let candidate = self.douglas_peucker(epsilon);
+                if candidate.iter().filter(|&&k| k).count() <= target {
+                    keep = candidate;
+                    high = epsilon;
+                } else {
+                    low = epsilon;
+                }
+            }
+        }
+        let mut points: Vec<Vec4<f32>> = Vec::new();
+        for (point, k) in self.points.iter().zip(keep) {
+            if k {
+                points.push(*point);
+            }
+        }
+        let mut path = Path::from_points(points);
+        path.original_point_count = self.original_point_count;
+        path
+    }
+    /// Simplify the polyline (using Visvalingam-Whyatt) by repeatedly
+    /// dropping the interior point with the smallest effective area (see
+    /// `least_significant_vertex`) until all remaining areas exceed
+    /// `min_area`. Both end points are always kept.
+    pub fn simplify_visvalingam(&self, min_area: f32) -> Path
+    {
+        let mut points = self.points.clone();
+        while points.len() > 2 {
+            let mut least = (0, f32::MAX);
+            for i in 1..(points.len() - 1) {
+                let area = triangle_area(points[i-1], points[i], points[i+1]);
+                if area < least.1 {
+                    least = (i, area);
+                }
+            }
+            if least.1 > min_area {
+                break;
+            }
+            points.remove(least.0);
+        }
+        let mut path = Path::from_points(points);
+        path.original_point_count = self.original_point_count;
+        path
+    }
+    /// Keep a point each time the accumulated turning angle (since the
+    /// last kept point) reaches `delta_angle`, so straight parts get no
+    /// points in between while bends keep their detail. Both end points
+    /// are always kept.
+    pub fn resample_by_turning(&self, delta_angle: f32) -> Path
+    {
+        let mut points: Vec<Vec4<f32>> = Vec::new();
+        let m = self.points.len();
+        let mut turned = 0.0;
+        for i in 0..m {
+            if i > 0 && i + 1 < m {
+                let cos = na::dot(&self.segment_direction(i-1),
+                                  &self.segment_direction(i));
+                turned += cos.clamp(-1.0, 1.0).acos();
+                if turned < delta_angle {
+                    continue;
+                }
+                turned = 0.0;
+            }
+            points.push(self.points[i]);
+        }
+        let mut path = Path::from_points(points);
+        path.original_point_count = self.original_point_count;
+        path
+    }
+    /// Cut the polyline (treated as a closed loop) open at normalized
+    /// parameter `t` of the loop. The resulting path starts and ends at
+    /// the cut point and runs once around the loop.
+    pub fn open_at(&self, t: f32) -> Path
+    {
+        if self.points.is_empty() {
+            return Path::from_points(Vec::new());
+        }
+        let mut ring = self.points.clone();
+        if ring[0] != ring[ring.len()-1] {
+            ring.push(ring[0]);
+        }
+        let ring = Path::from_points(ring);
+        let lvalues = ring.cumulative_lengths();
+        let length = lvalues[lvalues.len()-1];
+        let d = t.clamp(0.0, 1.0) * length;
+        concat(vec![ring.sub_path(&lvalues, d, length),
+                    ring.sub_path(&lvalues, 0.0, d)])
+    }
+    /// Cut the polyline into consecutive pieces of length `chunk_len`
+    /// (the last one might be shorter), interpolating new points where
+    /// the cuts fall within a segment.
+    pub fn chunks_by_length(&self, chunk_len: f32) -> Vec<Path>
+    {
+        let mut chunks: Vec<Path> = Vec::new();
+        if self.points.is_empty() {
+            return chunks;
+        }
+        let lvalues = self.cumulative_lengths();
+        let length = lvalues[lvalues.len()-1];
+        if chunk_len <= 0.0 || length <= chunk_len {
+            chunks.push(self.sub_path(&lvalues, 0.0, length));
+            return chunks;
+        }
+        let count = (length / chunk_len).ceil() as usize;
+        for i in 0..count {
+            let start = chunk_len * (i as f32);
+            let end = (chunk_len * ((i+1) as f32)).min(length);
+            chunks.push(self.sub_path(&lvalues, start, end));
+        }
+        chunks
+    }
+    /// Cut the polyline into `k` consecutive pieces of equal length,
+    /// interpolating new points where the cuts fall within a segment.
+    pub fn partition_equal_length(&self, k: usize) -> Vec<Path>
+    {
+        let mut pieces: Vec<Path> = Vec::with_capacity(k);
+        if self.points.is_empty() {
+            return pieces;
+        }
+        let lvalues = self.cumulative_lengths();
+        let cuts = spaced_distances(lvalues[lvalues.len()-1], k + 1);
+        for pair in cuts.windows(2) {
+            pieces.push(self.sub_path(&lvalues, pair[0], pair[1]));
+        }
+        pieces
+    }
+    /// Angle (in radians, between 0 and pi) within the XY plane between
+    /// the tangent at normalized parameter `t` and `reference`.
+    pub fn angle_to_at(&self, t: f32, reference: Vec4<f32>) -> f32
+    {
+        if self.points.is_empty() {
+            return 0.0;
+        }
+        let lvalues = self.cumulative_lengths();
+        let length = lvalues[lvalues.len()-1];
+        let (_point, tangent) = self.frame(&lvalues, t.clamp(0.0, 1.0) * length);
+        let norms = (tangent.x * tangent.x + tangent.y * tangent.y).sqrt() *
+            (reference.x * reference.x + reference.y * reference.y).sqrt();
+        if norms <= 0.0 {
+            return 0.0;
+        }
+        let dot = tangent.x * reference.x + tangent.y * reference.y;
+        (dot / norms).clamp(-1.0, 1.0).acos()
+    }
+    /// Tangent direction at normalized parameter `t` which (unlike the
+    /// direction of the segment) changes continuously: within the
+    /// first and last half of a segment it's blended with the average
+    /// of this and the neighbouring segment's direction, reaching that
+    /// average at the shared point.
+    pub fn smooth_tangent_at(&self, t: f32) -> Vec4<f32>
+    {
+        let lvalues = self.cumulative_lengths();
+        let length = lvalues[lvalues.len()-1];
+        self.smooth_tangent(&lvalues, t.clamp(0.0, 1.0) * length)
+    }
+    /// Distribute `n` points along a polyline (like `frames`) and pair
+    /// each of them with the tangent and a normal of a rotation
+    /// minimizing frame (calculated by the double reflection method),
+    /// so the normal doesn't twist around the tangent. For closed paths
+    /// (where the last point repeats the first one) the remaining twist
+    /// is distributed along the path, so the normals match at the
+    /// start and the end.
+    pub fn rmf_frames(&self, n: usize) ->
+        Vec<(Vec4<f32>, Vec4<f32>, Vec4<f32>)>
+    {
+        let mut rmf: Vec<(Vec4<f32>, Vec4<f32>, Vec4<f32>)> =
+            Vec::with_capacity(n);
+        let frames = self.frames(n);
+        if frames.is_empty() {
+            return rmf;
+        }
+        // initial normal perpendicular to the tangent (within the XY
+        // plane if possible)
+        let t0 = xyz(frames[0].1);
+        let mut up = Vec3::new(0.0, 0.0, 1.0);
+        if na::norm(&na::cross(&up, &t0)) < 1.0e-3 {
+            up = Vec3::new(1.0, 0.0, 0.0);
+        }
+        let mut normal = na::cross(&up, &t0);
+        if na::norm(&normal) > 0.0 {
+            normal = na::normalize(&normal);
+        }
+        let mut normals: Vec<Vec3<f32>> = Vec::with_capacity(frames.len());
+        normals.push(normal);
+        for i in 1..frames.len() {
+            let v1 = xyz(frames[i].0 - frames[i-1].0);
+            let c1 = na::dot(&v1, &v1);
+            let r = reflect(normals[i-1], v1, c1);
+            let t = reflect(xyz(frames[i-1].1), v1, c1);
+            let v2 = xyz(frames[i].1) - t;
+            let c2 = na::dot(&v2, &v2);
+            normals.push(reflect(r, v2, c2));
+        }
+        // distribute remaining twist for closed paths
+        let last = self.points.len() - 1;
+        if last > 0 && n > 1 && self.points[0] == self.points[last] {
+            let v2 = t0 - xyz(frames[frames.len()-1].1);
+            let r = reflect(normals[normals.len()-1], v2, na::dot(&v2, &v2));
+            let angle = na::dot(&na::cross(&normals[0], &r), &t0)
+                .atan2(na::dot(&normals[0], &r));
+            for i in 1..normals.len() {
+                let theta = -angle * (i as f32) / ((normals.len()-1) as f32);
+                let (sin, cos) = theta.sin_cos();
+                let t = xyz(frames[i].1);
+                normals[i] = normals[i] * cos + na::cross(&t, &normals[i]) * sin;
+            }
+        }
+        for (frame, normal) in frames.iter().zip(normals) {
+            rmf.push((frame.0, frame.1,
+                      Vec4::new(normal.x, normal.y, normal.z, 0.0)));
+        }
+        rmf
+    }
+    /// Orientations at `n` evenly spaced samples which turn the x axis
+    /// into the smoothed tangent (see `smooth_tangent_at`). Each one is
+    /// derived from the previous orientation by the shortest rotation
+    /// between consecutive tangents, so there are no sudden flips.
+    pub fn orientation_frames(&self, n: usize) -> Vec<UnitQuat<f32>>
+    {
+        let mut orientations: Vec<UnitQuat<f32>> = Vec::with_capacity(n);
+        let mut previous = Vec3::new(1.0, 0.0, 0.0);
+        let mut orientation: UnitQuat<f32> = na::one();
+        let lvalues = self.cumulative_lengths();
+        for d in spaced_distances(lvalues[lvalues.len()-1], n) {
+            let tangent = xyz(self.smooth_tangent(&lvalues, d));
+            if na::norm(&tangent) > 0.0 {
+                orientation = rotation_between(previous, tangent) * orientation;
+                previous = tangent;
+            }
+            orientations.push(orientation);
+        }
+        orientations
+    }
+    /// Compare a resampled (e.g. smoothed) `curve` against this
+    /// polyline: each original point is matched (in order) with the
+    /// nearest point of `curve` and for every original segment the
+    /// ratio of its chord length to the arc length of `curve` between
+    /// the matching points is returned. A ratio of 1 means the curve
+    /// follows the chord, smaller values indicate a strongly bent curve
+    /// (and maybe too few original points).
+    pub fn segment_straightness(&self, curve: &Path) -> Vec<f32>
+    {
+        let mut ratios: Vec<f32> = Vec::new();
+        if self.points.len() < 2 || curve.points.is_empty() {
+            return ratios;
+        }
+        // match original points with points of the curve
+        let mut matches: Vec<usize> = Vec::with_capacity(self.points.len());
+        let mut start = 0;
+        for point in &self.points {
+            let mut best = start;
+            let mut best_dist = f32::MAX;
+            for j in start..curve.points.len() {
+                let vector: Vec4<f32> = curve.points[j] - *point;
+                let dist = na::norm(&vector);
+                if dist < best_dist {
+                    best = j;
+                    best_dist = dist;
+                }
+            }
+            matches.push(best);
+            start = best;
+        }
+        let lvalues = curve.cumulative_lengths();
+        for i in 1..self.points.len() {
+            let vector: Vec4<f32> = self.points[i] - self.points[i-1];
+            let chord = na::norm(&vector);
+            let arc = lvalues[matches[i]] - lvalues[matches[i-1]];
+            if arc > 0.0 {
+                ratios.push(chord / arc);
+            } else {
+                ratios.push(1.0);
+            }
+        }
+        ratios
+    }
+    /// Axis-aligned bounding box of all points, returned as the
+    /// component-wise minimum and maximum.
+    pub fn aabb(&self) -> (Vec4<f32>, Vec4<f32>)
+    {
+        if self.points.is_empty() {
+            return (na::zero(), na::zero());
+        }
+        let mut min = self.points[0];
+        let mut max = self.points[0];
+        for point in &self.points {
+            min = Vec4::new(min.x.min(point.x), min.y.min(point.y),
+                            min.z.min(point.z), min.w.min(point.w));
+            max = Vec4::new(max.x.max(point.x), max.y.max(point.y),
+                            max.z.max(point.z), max.w.max(point.w));
+        }
+        (min, max)
+    }
+    /// Bounding sphere containing all points, returned as center (the
+    /// center of the bounding box) and radius.
+    pub fn bounding_sphere(&self) -> (Vec4<f32>, f32)
+    {
+        let (min, max) = self.aabb();
+        let center = (min + max) * 0.5;
+        let mut radius = 0.0;
+        for point in &self.points {
+            let vector: Vec4<f32> = *point - center;
+            radius = na::norm(&vector).max(radius);
+        }
+        (center, radius)
+    }
+    /// Centroid of the polyline treated as a wire of uniform density
+    /// (the midpoints of all segments weighted by their length). Falls
+    /// back to the first point for polylines without length.
+    pub fn length_weighted_centroid(&self) -> Vec4<f32>
+    {
+        if self.points.is_empty() {
+            return na::zero();
+        }
+        let mut sum: Vec4<f32> = na::zero();
+        let mut length = 0.0;
+        for i in 1..self.points.len() {
+            let vector: Vec4<f32> = self.points[i] - self.points[i-1];
+            let segment_length = na::norm(&vector);
+            sum = sum + (self.points[i-1] + self.points[i]) * (0.5 * segment_length);
+            length += segment_length;
+        }
+        if length > 0.0 {
+            sum / length
+        } else {
+            self.points[0]
+        }
+    }
+    /// Radius of gyration of the points: the root mean square distance
+    /// from their centroid.
+    pub fn radius_of_gyration(&self) -> f32
+    {
+        if self.points.is_empty() {
+            return 0.0;
+        }
+        let n = self.points.len() as f32;
+        let mut centroid: Vec4<f32> = na::zero();
+        for point in &self.points {
+            centroid = centroid + *point / n;
+        }
+        let mut sum = 0.0;
+        for point in &self.points {
+            sum += na::sqnorm(&(*point - centroid)) / n;
+        }
+        sum.sqrt()
+    }
+    /// Moment of inertia (within the XY plane) of the polyline treated
+    /// as a wire of unit density, about the axis through `axis_point`
+    /// perpendicular to the plane (the integral of the squared distance
+    /// from `axis_point` along the polyline).
+    pub fn moment_of_inertia_2d(&self, axis_point: Vec4<f32>) -> f32
+    {
+        let mut moment = 0.0;
+        for i in 1..self.points.len() {
+            let (ox, oy) = (self.points[i-1].x - axis_point.x,
+                            self.points[i-1].y - axis_point.y);
+            let (dx, dy) = (self.points[i].x - self.points[i-1].x,
+                            self.points[i].y - self.points[i-1].y);
+            let length = (dx * dx + dy * dy).sqrt();
+            // integral of |o + t d|^2 for t from 0 to 1
+            moment += length * (ox * ox + oy * oy + ox * dx + oy * dy +
+                                (dx * dx + dy * dy) / 3.0);
+        }
+        moment
+    }
+    /// Ratio of the length of the polyline to the diagonal of its
+    /// axis-aligned bounding box (close to 1 for a straight polyline,
+    /// large for a wiggly one). It's 0 if the box has no extent.
+    pub fn length_over_diagonal(&self) -> f32
+    {
+        let (min, max) = self.aabb();
+        let diagonal = na::norm(&(max - min));
+        if diagonal <= 0.0 {
+            return 0.0;
+        }
+        self.length_streaming() / diagonal
+    }
+    /// All points mapped into the unit cube relative to the
+    /// axis-aligned bounding box (w stays untouched). Sides of the box
+    /// without extent map to 0.5.
+    pub fn normalized_vertices(&self) -> Vec<Vec4<f32>>
+    {
+        let (min, max) = self.aabb();
+        let mut vertices: Vec<Vec4<f32>> = Vec::with_capacity(self.points.len());
+        for point in &self.points {
+            vertices.push(Vec4::new(unit_interval(point.x, min.x, max.x),
+                                    unit_interval(point.y, min.y, max.y),
+                                    unit_interval(point.z, min.z, max.z),
+                                    point.w));
+        }
+        vertices
+    }
+    /// Ratio of the wider to the narrower side of the axis-aligned
+    /// bounding box within the XY plane (infinite for paths along the x
+    /// or y axis, 1 for a single point).
+    pub fn aspect_ratio_2d(&self) -> f32
+    {
+        let (min, max) = self.aabb();
+        let width = max.x - min.x;
+        let height = max.y - min.y;
+        let wider = width.max(height);
+        let narrower = width.min(height);
+        if wider <= 0.0 {
+            1.0
+        } else if narrower <= 0.0 {
+            f32::INFINITY
+        } else {
+            wider / narrower
+        }
+    }
+    /// Oriented bounding box of all points within the XY plane, aligned
+    /// with the principal axis of the points. Returns the center (with
+    /// z = 0 and w = 1), the half extents along the principal axis and
+    /// perpendicular to it, and the rotation (in radians) of the
+    /// principal axis against the x axis.
+    pub fn obb_2d(&self) -> (Vec4<f32>, f32, f32, f32)
+    {
+        if self.points.is_empty() {
+            return (Vec4::new(0.0, 0.0, 0.0, 1.0), 0.0, 0.0, 0.0);
+        }
+        // principal axis from the covariance of the points
+        let n = self.points.len() as f32;
+        let mut mx = 0.0;
+        let mut my = 0.0;
+        for point in &self.points {
+            mx += point.x / n;
+            my += point.y / n;
+        }
+        let mut cxx = 0.0;
+        let mut cyy = 0.0;
+        let mut cxy = 0.0;
+        for point in &self.points {
+            cxx += (point.x - mx) * (point.x - mx);
+            cyy += (point.y - my) * (point.y - my);
+            cxy += (point.x - mx) * (point.y - my);
+        }
+        let angle = 0.5 * (2.0 * cxy).atan2(cxx - cyy);
+        let (sin, cos) = angle.sin_cos();
+        // extents along both axes
+        let mut min = (f32::MAX, f32::MAX);
+        let mut max = (f32::MIN, f32::MIN);
+        for point in &self.points {
+            let u = (point.x - mx) * cos + (point.y - my) * sin;
+            let v = (point.y - my) * cos - (point.x - mx) * sin;
+            min = (min.0.min(u), min.1.min(v));
+            max = (max.0.max(u), max.1.max(v));
+        }
+        let u = (min.0 + max.0) * 0.5;
+        let v = (min.1 + max.1) * 0.5;
+        let center = Vec4::new(mx + u * cos - v * sin, my + u * sin + v * cos,
+                               0.0, 1.0);
+        (center, (max.0 - min.0) * 0.5, (max.1 - min.1) * 0.5, angle)
+    }
+    /// Minimum and maximum Feret diameter (caliper width) of the points
+    /// within the XY plane, measured along `angle_steps` directions
+    /// evenly spread over half a turn.
+    pub fn feret_diameters_2d(&self, angle_steps: usize) -> (f32, f32)
+    {
+        if self.points.is_empty() || angle_steps == 0 {
+            return (0.0, 0.0);
+        }
+        let mut min_width = f32::MAX;
+        let mut max_width = 0.0f32;
+        for k in 0..angle_steps {
+            let angle = std::f32::consts::PI * (k as f32) / (angle_steps as f32);
+            let (sin, cos) = angle.sin_cos();
+            let mut min = f32::MAX;
+            let mut max = f32::MIN;
+            for point in &self.points {
+                let projection = point.x * cos + point.y * sin;
+                min = min.min(projection);
+                max = max.max(projection);
+            }
+            min_width = min_width.min(max - min);
+            max_width = max_width.max(max - min);
+        }
+        (min_width, max_width)
+    }
+    /// Smallest circle containing all points projected onto the XY
+    /// plane (using Welzl's algorithm in its iterative form). Returns
+    /// the center (with z = 0 and w = 1) and the radius.
+    pub fn min_enclosing_circle_2d(&self) -> (Vec4<f32>, f32)
+    {
+        let points = &self.points;
+        if points.is_empty() {
+            return (Vec4::new(0.0, 0.0, 0.0, 1.0), 0.0);
+        }
+        let outside = |circle: &(Vec4<f32>, f32), point: Vec4<f32>| {
+            distance_2d(circle.0, point) > circle.1 * (1.0 + 1.0e-5) + 1.0e-6
+        };
+        let mut circle = circle_2d(points[0], points[0]);
+        for i in 1..points.len() {
+            if !outside(&circle, points[i]) {
+                continue;
+            }
+            // points[i] lies on the boundary
+            circle = circle_2d(points[i], points[i]);
+            for j in 0..i {
+                if !outside(&circle, points[j]) {
+                    continue;
+                }
+                // points[i] and points[j] lie on the boundary
+                circle = circle_2d(points[i], points[j]);
+                for k in 0..j {
+                    if outside(&circle, points[k]) {
+                        circle = circumcircle_2d(points[i], points[j], points[k]);
+                    }
+                }
+            }
+        }
+        circle
+    }
+    /// Convex hull of all points projected onto the XY plane (using
+    /// Andrew's monotone chain algorithm), in counter-clockwise order.
+    /// Points with a NaN or infinite x or y are ignored.
+    pub fn convex_hull_2d(&self) -> Vec<Vec4<f32>>
+    {
+        let mut points: Vec<Vec4<f32>> = self.points.iter()
+            .filter(|point| point.x.is_finite() && point.y.is_finite())
+            .cloned()
+            .collect();
+        points.sort_by(|a, b| (a.x, a.y).partial_cmp(&(b.x, b.y)).unwrap());
+        points.dedup_by(|a, b| a.x == b.x && a.y == b.y);
+        if points.len() < 3 {
+            return points;
+        }
+        let mut hull: Vec<Vec4<f32>> = Vec::with_capacity(2 * points.len());
+        // lower hull
+        for point in &points {
+            while hull.len() >= 2 &&
+                cross_2d(hull[hull.len()-2], hull[hull.len()-1], *point) <= 0.0 {
+                hull.pop();
+            }
+            hull.push(*point);
+        }
+        // upper hull
+        let lower = hull.len() + 1;
+        for point in points.iter().rev().skip(1) {
+            while hull.len() >= lower &&
+                cross_2d(hull[hull.len()-2], hull[hull.len()-1], *point) <= 0.0 {
+                hull.pop();
+            }
+            hull.push(*point);
+        }
+        // the first point was added again
+        hull.pop();
+        hull
+    }
+    /// Extent of the polyline along its chord (the direction from the
+    /// first to the last point) and the maximum distance of any point
+    /// from the line through the chord.
+    pub fn chord_aligned_bounds(&self) -> (f32, f32)
+    {
+        if self.points.is_empty() {
+            return (0.0, 0.0);
+        }
+        let first = self.points[0];
+        let last = self.points[self.points.len()-1];
+        let chord: Vec4<f32> = last - first;
+        let chord_length = na::norm(&chord);
+        let mut min = 0.0f32;
+        let mut max = 0.0f32;
+        let mut deviation = 0.0f32;
+        for point in &self.points {
+            if chord_length > 0.0 {
+                let along = na::dot(&(*point - first), &chord) / chord_length;
+                min = min.min(along);
+                max = max.max(along);
+            }
+            deviation = deviation.max(line_distance(first, last, *point));
+        }
+        (max - min, deviation)
+    }
+    /// Sinuosity of the polyline: its length divided by the distance
+    /// between the first and the last point (1 for a straight line,
+    /// infinite for closed loops).
+    pub fn sinuosity(&self) -> f32
+    {
+        let length = self.length_streaming();
+        if length <= 0.0 {
+            return 1.0;
+        }
+        let chord: Vec4<f32> = self.points[self.points.len()-1] - self.points[0];
+        let chord_length = na::norm(&chord);
+        if chord_length <= 0.0 {
+            return f32::INFINITY;
+        }
+        length / chord_length
+    }
+    /// Minimum and maximum of the projections of all points onto the
+    /// (normalized) direction `axis`. The w of `axis` is ignored.
+    pub fn extent_along(&self, axis: Vec4<f32>) -> (f32, f32)
+    {
+        let mut axis = axis;
+        axis.w = 0.0;
+        if self.points.is_empty() || na::norm(&axis) <= 0.0 {
+            return (0.0, 0.0);
+        }
+        let axis = na::normalize(&axis);
+        let mut min = f32::MAX;
+        let mut max = f32::MIN;
+        for point in &self.points {
+            let projection = na::dot(point, &axis);
+            min = min.min(projection);
+            max = max.max(projection);
+        }
+        (min, max)
+    }
+    /// Calculate the diameter of a polyline, the maximum distance
+    /// between any two of its points.
+    pub fn diameter(&self) -> f32
+    {
+        let mut diameter = 0.0;
+        for i in 0..self.points.len() {
+            for j in (i+1)..self.points.len() {
+                let vector: Vec4<f32> = self.points[j] - self.points[i];
+                let dist = na::norm(&vector);
+                if dist > diameter {
+                    diameter = dist;
+                }
+            }
+        }
+        diameter
+    }
+    /// Export the polyline in Wavefront OBJ format: one `v` line per
+    /// point (dropping `w`) followed by a single `l` element connecting
+    /// all of them.
+    pub fn to_obj(&self) -> String
+    {
+        let mut obj = String::new();
+        for point in &self.points {
+            obj.push_str(&format!("v {} {} {}\n", point.x, point.y, point.z));
+        }
+        if self.points.len() > 1 {
+            obj.push('l');
+            for i in 0..self.points.len() {
+                obj.push_str(&format!(" {}", i + 1));
+            }
+            obj.push('\n');
+        }
+        obj
+    }
+    /// Index pairs of all segments (to be used together with `points()`
+    /// for indexed drawing of lines).
+    pub fn line_indices(&self) -> Vec<[u32; 2]>
+    {
+        let mut indices: Vec<[u32; 2]> = Vec::new();
+        for i in 1..self.points.len() {
+            indices.push([(i-1) as u32, i as u32]);
+        }
+        indices
+    }
+    /// Index pairs of all segments (like `line_indices`) including the
+    /// closing segment from the last point back to the first one.
+    pub fn line_indices_closed(&self) -> Vec<[u32; 2]>
+    {
+        let mut indices = self.line_indices();
+        if self.points.len() > 2 {
+            indices.push([(self.points.len()-1) as u32, 0]);
+        }
+        indices
+    }
+    /// Repair the polyline in one pass: drop points with NaN or infinite
+    /// components as well as points within `epsilon` of the previous
+    /// (kept) point, then rebuild the cache. Returns how many points
+    /// were dropped for either reason.
+    pub fn sanitize(&mut self, epsilon: f32) -> SanitizeReport
+    {
+        let mut report = SanitizeReport { non_finite: 0, duplicates: 0 };
+        let mut points: Vec<Vec4<f32>> = Vec::with_capacity(self.points.len());
+        let mut params: Vec<f32> = Vec::new();
+        for (i, point) in self.points.iter().enumerate() {
+            if !(point.x.is_finite() && point.y.is_finite() &&
+                 point.z.is_finite() && point.w.is_finite()) {
+                report.non_finite += 1;
+                continue;
+            }
+            if let Some(&previous) = points.last() {
+                let vector: Vec4<f32> = *point - previous;
+                if na::norm(&vector) <= epsilon {
+                    report.duplicates += 1;
+                    continue;
+                }
+            }
+            points.push(*point);
+            if let Some(ref old) = self.params {
+                params.push(old[i]);
+            }
+        }
+        self.points = points;
+        if self.params.is_some() {
+            self.params = Some(params);
+        }
+        self.invalidate();
+        self.length();
+        report
+    }
+    /// Insert a new point at the start of the polyline (a point which
+    /// is identical to the current start is silently skipped).
+    pub fn prepend(&mut self, point: Vec4<f32>)
+    {
+        if !self.points.is_empty() && self.points[0] == point {
+            return;
+        }
+        self.points.insert(0, point);
+        // there is no parameter for the new point
+        self.params = None;
+        self.invalidate();
+    }
+    /// Append the points of `other`, reversed if its last point is
+    /// closer to the end of this polyline than its first one. A point
+    /// identical to the current end is skipped.
+    pub fn join_nearest(&mut self, other: &Path)
+    {
+        if other.points.is_empty() {
+            return;
+        }
+        let mut points = other.points.clone();
+        if let Some(&tail) = self.points.last() {
+            let to_first: Vec4<f32> = points[0] - tail;
+            let to_last: Vec4<f32> = points[points.len()-1] - tail;
+            if na::sqnorm(&to_last) < na::sqnorm(&to_first) {
+                points.reverse();
+            }
+        }
+        for point in points {
+            if self.points.last() != Some(&point) {
+                self.points.push(point);
+            }
+        }
+        // there are no parameters for the new points
+        self.params = None;
+        self.invalidate();
+    }
+    /// Trim the polyline to the given length `max_len` by dropping
+    /// points beyond and interpolating a new end point (if required).
+    /// Nothing happens if the polyline is shorter anyway.
+    pub fn truncate_to_length(&mut self, max_len: f32)
+    {
+        let lvalues = self.cumulative_lengths();
+        if self.points.len() < 2 || max_len >= lvalues[lvalues.len()-1] {
+            return;
+        }
+        let point = self.interpolate(&lvalues, max_len);
+        let (index, t) = find_segment(&lvalues, max_len);
+        self.points.truncate(index+1);
+        if let Some(ref mut params) = self.params {
+            let param = params[index] * (1.0 - t) + params[index+1] * t;
+            params.truncate(index+1);
+            if self.points[index] != point {
+                params.push(param);
+            }
+        }
+        if self.points[index] != point {
+            self.points.push(point);
+        }
+        self.invalidate();
+    }
+    /// Replace the points of the polyline by `n` points distributed
+    /// along it (see `evaluate`) and rebuild the cached lengths.
+    pub fn resample_in_place(&mut self, n: usize)
+    {
+        if self.points.is_empty() {
+            return;
+        }
+        self.points = self.sample(n);
+        // parameters don't match the new points anymore
+        self.params = None;
+        self.invalidate();
+        self.length();
+    }
+    /// Project `q` onto the closest segment of the polyline and insert
+    /// the projected point there as a new point (unless it coincides
+    /// with one of the existing points).
+    pub fn insert_at_projection(&mut self, q: Vec4<f32>)
+    {
+        if self.points.len() < 2 {
+            return;
+        }
+        let (index, t, point) = self.project(q);
+        if point == self.points[index] || point == self.points[index+1] {
+            return;
+        }
+        self.points.insert(index+1, point);
+        if let Some(ref mut params) = self.params {
+            let param = params[index] * (1.0 - t) + params[index+1] * t;
+            params.insert(index+1, param);
+        }
+        self.invalidate();
+    }
+    /// Rotate all points by `angle` (in radians) within the XY plane
+    /// around the XY position of `pivot` (z and w stay untouched).
+    pub fn rotate_2d(&mut self, angle: f32, pivot: Vec4<f32>)
+    {
+        let (sin, cos) = angle.sin_cos();
+        for point in &mut self.points {
+            let x = point.x - pivot.x;
+            let y = point.y - pivot.y;
+            point.x = pivot.x + x * cos - y * sin;
+            point.y = pivot.y + x * sin + y * cos;
+        }
+        self.invalidate();
+    }
+    /// Reverse the direction of the polyline. Retained parameters (see
+    /// `params`) are mirrored within their range, so they still
+    /// increase along the (reversed) polyline.
+    pub fn reverse_params(&mut self)
+    {
+        self.points.reverse();
+        if let Some(ref mut params) = self.params {
+            if let (Some(&first), Some(&last)) = (params.first(), params.last()) {
+                params.reverse();
+                for param in params.iter_mut() {
+                    *param = first + last - *param;
+                }
+            }
+        }
+        self.invalidate();
+    }
+    /// Reverse the order of the points with indices from `i` to `j`
+    /// (inclusive). Panics unless `i <= j` and `j` is a valid index.
+    /// Any parameters given by `add_sorted_point` are dropped.
+    pub fn reverse_range(&mut self, i: usize, j: usize)
+    {
+        assert!(i <= j && j < self.points.len(),
+                "invalid range {}..={} for {} points", i, j, self.points.len());
+        self.points[i..j+1].reverse();
+        // the (sorted) parameters no longer match the points
+        self.params = None;
+        self.invalidate();
+    }
+    /// Translate the polyline so that its first point lies at the
+    /// origin (w stays untouched). Translation doesn't change any
+    /// length, so the cache stays valid.
+    pub fn rebase_to_origin(&mut self)
+    {
+        if let Some(&first) = self.points.first() {
+            for point in &mut self.points {
+                point.x -= first.x;
+                point.y -= first.y;
+                point.z -= first.z;
+            }
+        }
+    }
+    /// Mirror all points across the plane through the origin which is
+    /// perpendicular to `axis` (by negating that component).
+    pub fn mirror(&mut self, axis: Axis)
+    {
+        for point in &mut self.points {
+            match axis {
+                Axis::X => point.x = -point.x,
+                Axis::Y => point.y = -point.y,
+                Axis::Z => point.z = -point.z,
+            }
+        }
+        self.invalidate();
+    }
+    /// Clamp all points (component-wise) into the axis-aligned box
+    /// spanned by `min` and `max` (w stays untouched).
+    pub fn clamp_to_aabb(&mut self, min: Vec4<f32>, max: Vec4<f32>)
+    {
+        for point in &mut self.points {
+            point.x = point.x.max(min.x).min(max.x);
+            point.y = point.y.max(min.y).min(max.y);
+            point.z = point.z.max(min.z).min(max.z);
+        }
+        self.invalidate();
+    }
+    /// Iterate over the points of the polyline, each paired with its
+    /// distance from the start.
+    pub fn iter_with_distance<'a>(&'a self) ->
+        impl Iterator<Item = (f32, Vec4<f32>)> + 'a
+    {
+        self.cumulative_lengths().into_iter().zip(self.points.iter().cloned())
+    }
+    /// Access the points of the polyline.
+    pub fn points(&self) -> &[Vec4<f32>]
+    {
+        &self.points
+    }
+    /// Normalized direction of the segment starting at point `index`
+    /// (or a zero vector for a segment without length).
+    fn segment_direction(&self, index: usize) -> Vec4<f32>
+    {
+        let vector: Vec4<f32> = self.points[index+1] - self.points[index];
+        let length = na::norm(&vector);
+        if length > 0.0 {
+            vector / length
+        } else {
+            na::zero()
+        }
+    }
+    /// Ratio between the current number of points and the number of
+    /// points the path was finalized with. The original count is kept
+    /// when the path is simplified in place (e.g. by
+    /// `resample_in_place`), so this tells how many points were
+    /// dropped.
+    pub fn reduction_ratio(&self) -> f32
+    {
+        if self.original_point_count == 0 {
+            return 1.0;
+        }
+        (self.points.len() as f32) / (self.original_point_count as f32)
+    }
+    /// Access the (sorted) parameters of a polyline which was built by
+    /// `add_sorted_point` (or `None` if the points were added in order).
+    pub fn params(&self) -> Option<&[f32]>
+    {
+        self.params.as_deref()
+    }
+    /// Mark the cached lengths as outdated after the points changed.
+    fn invalidate(&mut self)
+    {
+        self.lvalues.clear();
+        self.changed = true;
+    }
+    /// Cumulative lengths of the polyline (the distance from the start
+    /// for each point), either taken from the cache or (if the cache is
+    /// outdated) calculated on the fly.
+    pub fn cumulative_lengths(&self) -> Vec<f32>
+    {
+        if !self.changed {
+            return self.lvalues.clone();
+        }
+        let mut lvalues: Vec<f32> = Vec::with_capacity(self.points.len());
+        let mut length = 0.0;
+        lvalues.push(0.0);
+        for i in 1..self.points.len() {
+            let vector: Vec4<f32> = self.points[i] - self.points[i-1];
+            length += na::norm(&vector);
+            lvalues.push(length);
+        }
+        lvalues
+    }
+    /// Chord length parameters of all points: their distance from the
+    /// start divided by the length of the polyline (all 0 if it has no
+    /// length).
+    pub fn chord_parameters(&self) -> Vec<f32>
+    {
+        if self.points.is_empty() {
+            return Vec::new();
+        }
+        let mut lvalues = self.cumulative_lengths();
+        let length = lvalues[lvalues.len()-1];
+        if length > 0.0 {
+            for l in &mut lvalues {
+                *l /= length;
+            }
+        }
+        lvalues
+    }
+    /// Distribute `n` points along a polyline (like `evaluate`, but
+    /// without filling the cache).
+    fn sample(&self, n: usize) -> Vec<Vec4<f32>>
+    {
+        let lvalues = self.cumulative_lengths();
+        let mut points: Vec<Vec4<f32>> = Vec::with_capacity(n);
+        for d in spaced_distances(lvalues[lvalues.len()-1], n) {
+            points.push(self.interpolate(&lvalues, d));
+        }
+        points
+    }
+    /// Cumulative lengths of the polyline ignoring the `w` components
+    /// (which may store weights or radii instead of 1).
+    fn cumulative_lengths_xyz(&self) -> Vec<f32>
+    {
+        let mut lvalues: Vec<f32> = vec![0.0];
+        for i in 1..self.points.len() {
+            let mut vector: Vec4<f32> = self.points[i] - self.points[i-1];
+            vector.w = 0.0;
+            let length = lvalues[i-1] + na::norm(&vector);
+            lvalues.push(length);
+        }
+        lvalues
+    }
+    /// Extract the part of the polyline between the distances `d0` and
+    /// `d1` (with `d0` not beyond `d1`) as a new path.
+    fn sub_path(&self, lvalues: &[f32], d0: f32, d1: f32) -> Path
+    {
+        let mut points: Vec<Vec4<f32>> = Vec::new();
+        points.push(self.interpolate(lvalues, d0));
+        for (point, l) in self.points.iter().zip(lvalues) {
+            if *l > d0 && *l < d1 {
+                points.push(*point);
+            }
+        }
+        points.push(self.interpolate(lvalues, d1));
+        Path::from_points(points)
+    }
+    /// Mark the points which are kept by Douglas-Peucker simplification
+    /// with tolerance `epsilon` (the end points are always kept).
+    fn douglas_peucker(&self, epsilon: f32) -> Vec<bool>
+    {
+        let mut keep: Vec<bool> = vec![false; self.points.len()];
+        if self.points.is_empty() {
+            return keep;
+        }
+        let last = self.points.len() - 1;
+        keep[0] = true;
+        keep[last] = true;
+        let mut ranges: Vec<(usize, usize)> = vec![(0, last)];
+        while let Some((first, last)) = ranges.pop() {
+            let mut index = first;
+            let mut max = 0.0;
+            for i in (first+1)..last {
+                let distance = line_distance(self.points[first],
+                                             self.points[last],
+                                             self.points[i]);
+                if distance > max {
+                    index = i;
+                    max = distance;
+                }
+            }
+            if max > epsilon {
+                keep[index] = true;
+                ranges.push((first, index));
+                ranges.push((index, last));
+            }
+        }
+        keep
+    }
+    /// Control points of the open Catmull-Rom spline segment from
+    /// point `j` to point `j+1` (repeating the end points).
+    fn catmull_rom_segment(&self, j: usize)
+                           -> (Vec4<f32>, Vec4<f32>, Vec4<f32>, Vec4<f32>)
+    {
+        let last = self.points.len() - 1;
+        (self.points[j.max(1) - 1],
+         self.points[j],
+         self.points[j+1],
+         self.points[(j+2).min(last)])
+    }
+    /// Smoothed tangent (see `smooth_tangent_at`) at distance `d` along
+    /// the polyline.
+    fn smooth_tangent(&self, lvalues: &[f32], d: f32) -> Vec4<f32>
+    {
+        if self.points.len() < 2 {
+            return na::zero();
+        }
+        let (index, s) = find_segment(lvalues, d);
+        let current = self.segment_direction(index);
+        let neighbour = if s < 0.5 {
+            if index == 0 {
+                current
+            } else {
+                self.segment_direction(index - 1)
+            }
+        } else if index + 2 == self.points.len() {
+            current
+        } else {
+            self.segment_direction(index + 1)
+        };
+        // weight of the neighbour: 1/2 at the shared point, 0 halfway
+        let weight = (s - 0.5).abs();
+        let tangent = current * (1.0 - weight) + neighbour * weight;
+        if na::norm(&tangent) > 0.0 {
+            na::normalize(&tangent)
+        } else {
+            current
+        }
+    }
+    /// Interpolate the point at distance `d` along the (non-empty)
+    /// polyline together with the tangent direction of its segment.
+    fn frame(&self, lvalues: &[f32], d: f32) -> (Vec4<f32>, Vec4<f32>)
+    {
+        if self.points.len() < 2 {
+            return (self.points[0], na::zero());
+        }
+        let (index, t) = find_segment(lvalues, d);
+        let point = self.points[index] * (1.0 - t) + self.points[index+1] * t;
+        (point, self.segment_direction(index))
+    }
+    /// Interpolate the point at distance `d` along the polyline.
+    fn interpolate(&self, lvalues: &[f32], d: f32) -> Vec4<f32>
+    {
+        if self.points.len() < 2 {
+            return self.points[0];
+        }
+        let (index, t) = find_segment(lvalues, d);
+        self.points[index] * (1.0 - t) + self.points[index+1] * t
+    }
+}
+
+impl<'a> IntoIterator for &'a Path {
+    type Item = &'a Vec4<f32>;
+    type IntoIter = std::slice::Iter<'a, Vec4<f32>>;
+
+    fn into_iter(self) -> std::slice::Iter<'a, Vec4<f32>>
+    {
+        self.points.iter()
+    }
+}
+
+/// Errors reported while constructing a Path.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathError {
+    /// Two points were added with the same parameter, `index` refers
+    /// to the second of them (in sorted order).
+    DuplicateParam { index: usize },
+    /// The first two or the last two points coincide.
+    DegenerateBoundary,
+    /// Points were added both in order and sorted by parameter.
+    MixedBuildModes,
+    /// The station at `index` doesn't increase or doesn't match the
+    /// distance from the previous point.
+    InvalidStation { index: usize },
+    /// Expected one value per point (`expected` many), but got `found`.
+    LengthMismatch { expected: usize, found: usize },
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match *self {
+            PathError::DuplicateParam { index } =>
+                write!(f, "duplicate parameter for point {}", index),
+            PathError::DegenerateBoundary =>
+                write!(f, "first or last segment has no length"),
+            PathError::MixedBuildModes =>
+                write!(f, "points were added both in order and sorted"),
+            PathError::InvalidStation { index } =>
+                write!(f, "station {} doesn't match the geometry", index),
+            PathError::LengthMismatch { expected, found } =>
+                write!(f, "expected {} values, found {}", expected, found),
+        }
+    }
+}
+
+impl Error for PathError {}
+
+/// Helper to construct a Path.
+
+pub struct PathBuilder {
+    points: Vec<Vec4<f32>>,
+    sorted: Vec<Vec4<f32>>,
+    params: Vec<f32>,
+    max_points: Option<usize>,
+    overflowed: bool,
+}
+
+impl PathBuilder {
+    /// Prepares the creation of a path. Points can be added either in
+    /// order or by providing an additional parameter (which creates
+    /// that order once all points were added).
+    pub fn new() -> PathBuilder
+    {
+        PathBuilder { points: Vec::new(),
+                      sorted: Vec::new(),
+                      params: Vec::new(),
+                      max_points: None,
+                      overflowed: false,}
+    }
+    /// Like `new`, but accept at most `max` points. Any further points
+    /// are silently ignored (see `overflowed`).
+    pub fn with_max_points(max: usize) -> PathBuilder
+    {
+        let mut builder = PathBuilder::new();
+        builder.max_points = Some(max);
+        builder
+    }
+    /// Tells if points were ignored because the limit given to
+    /// `with_max_points` was reached.
+    pub fn overflowed(&self) -> bool
+    {
+        self.overflowed
+    }
+    /// Check the limit given to `with_max_points` before adding another
+    /// point.
+    fn accepts_point(&mut self) -> bool
+    {
+        if let Some(max) = self.max_points {
+            if self.points.len() + self.sorted.len() >= max {
+                self.overflowed = true;
+                return false;
+            }
+        }
+        true
+    }
+    /// Prepares a path with the `sides` corners of a regular polygon
+    /// (in the XY plane around `center`, counter-clockwise, starting on
+    /// the positive x axis). The polygon is not closed, the first point
+    /// is not repeated at the end.
+    pub fn regular_polygon(sides: usize, radius: f32, center: Vec4<f32>) ->
+        PathBuilder
+    {
+        let mut builder = PathBuilder::new();
+        for i in 0..sides {
+            let angle = 2.0 * std::f32::consts::PI * (i as f32) / (sides as f32);
+            let (sin, cos) = angle.sin_cos();
+            builder.add_point(Vec4::new(center.x + radius * cos,
+                                        center.y + radius * sin,
+                                        center.z,
+                                        center.w));
+        }
+        builder
+    }
+    /// Prepares a path with `n` points along a helix around the z axis,
+    /// starting at `(radius, 0, 0)` and rising by `pitch` per turn
+    /// (counter-clockwise for a positive number of `turns`).
+    pub fn helix(turns: f32, radius: f32, pitch: f32, n: usize) -> PathBuilder
+    {
+        let mut builder = PathBuilder::new();
+        for i in 0..n {
+            let mut u = 0.0;
+            if n > 1 {
+                u = turns * (i as f32) / ((n-1) as f32);
+            }
+            let (sin, cos) = (2.0 * std::f32::consts::PI * u).sin_cos();
+            builder.add_point(Vec4::new(radius * cos, radius * sin, pitch * u,
+                                        1.0));
+        }
+        builder
+    }
+    /// Prepares a path from stations, points paired with their intended
+    /// distance from the start. The distances have to increase and
+    /// their differences have to match the distances between the points
+    /// (within a relative tolerance of 0.1 percent).
+    pub fn from_stations(stations: &[(f32, Vec4<f32>)]) ->
+        Result<PathBuilder, PathError>
+    {
+        let mut builder = PathBuilder::new();
+        for (i, &(station, point)) in stations.iter().enumerate() {
+            if i > 0 {
+                let (previous_station, previous) = stations[i-1];
+                let delta = station - previous_station;
+                let vector: Vec4<f32> = point - previous;
+                if delta <= 0.0 ||
+                    (na::norm(&vector) - delta).abs() > 1.0e-3 * delta.max(1.0) {
+                    return Err(PathError::InvalidStation { index: i });
+                }
+            }
+            builder.add_point(point);
+        }
+        Ok(builder)
+    }
+    /// Add points in a particular order by repeatedly calling this
+    /// function.
+    pub fn add_point<P: IntoPoint>(&mut self, point: P) ->
+        &mut PathBuilder
+    {
+        if self.accepts_point() {
+            self.points.push(point.into_point());
+        }
+        self
+    }
+    /// Like `add_point`, but skip the point if it is within `epsilon` of
+    /// the previously added point.
+    pub fn add_point_dedup<P: IntoPoint>(&mut self, point: P, epsilon: f32)
+                                         -> &mut PathBuilder
+    {
+        let point = point.into_point();
+        if let Some(&last) = self.points.last() {
+            if na::norm(&(point - last)) <= epsilon {
+                return self;
+            }
+        }
+        self.add_point(point)
+    }
+    /// Add a point given in polar coordinates (within the XY plane) in
+    /// a particular order, see `add_point`.
+    pub fn add_polar(&mut self, radius: f32, angle: f32) ->
+        &mut PathBuilder
+    {
+        let (sin, cos) = angle.sin_cos();
+        self.add_point(Vec4::new(radius * cos, radius * sin, 0.0, 1.0))
+    }
+    /// Add points by calling this function repeatedly in any order,
+    /// but implicitly defining an order by providing a parameter for
+    /// each point.
+    pub fn add_sorted_point<P: IntoPoint>(&mut self, point: P, param: f32)
+                                          -> &mut PathBuilder
+    {
+        let point = point.into_point();
+        if !self.accepts_point() {
+            return self;
+        }
+        if self.sorted.is_empty() {
+            self.sorted.push(point);
+            self.params.push(param);
+        } else {
+            // find index to insert param
+            let mut was_inserted = false;
+            for i in 0..self.params.len() {
+                if param < self.params[i] {
+                    self.sorted.insert(i, point);
+                    self.params.insert(i, param);
+                    was_inserted = true;
+                    break;
+                }
+            }
+            if !was_inserted {
+                // append to end
+                self.sorted.push(point);
+                self.params.push(param);
+            }
+        }
+        self
+    }
+    /// Calculate the length of the polyline through the points added
+    /// so far (in the order `finalize` would use).
+    pub fn current_length(&self) -> f32
+    {
+        let points = if self.params.is_empty() {
+            &self.points
+        } else {
+            &self.sorted
+        };
+        let mut length = 0.0;
+        for i in 1..points.len() {
+            let vector: Vec4<f32> = points[i] - points[i-1];
+            length += na::norm(&vector);
+        }
+        length
+    }
+    /// Use either points which were added in that particular order or
+    /// use provided parameters to sort points added in arbitrary
+    /// order. The points are moved into the path (not copied), so their
+    /// capacity is kept.
+    pub fn finalize(self) -> Path
+    {
+        if self.params.is_empty() {
+            Path::from_points(self.points)
+        } else {
+            let mut path = Path::from_points(self.sorted);
+            path.params = Some(self.params);
+            path
+        }
+    }
+    /// Like `finalize`, but reject points which were added with
+    /// identical parameters (because their order would be ambiguous),
+    /// builders mixing `add_point` with `add_sorted_point`, and paths
+    /// whose first or last segment has no length.
+    pub fn try_finalize(self) -> Result<Path, PathError>
+    {
+        if !self.points.is_empty() && !self.sorted.is_empty() {
+            return Err(PathError::MixedBuildModes);
+        }
+        for i in 1..self.params.len() {
+            if self.params[i] == self.params[i-1] {
+                return Err(PathError::DuplicateParam { index: i });
+            }
+        }
+        let points = if self.params.is_empty() {
+            &self.points
+        } else {
+            &self.sorted
+        };
+        let n = points.len();
+        if n > 1 && (points[0] == points[1] || points[n-2] == points[n-1]) {
+            return Err(PathError::DegenerateBoundary);
+        }
+        Ok(self.finalize())
+    }
+    /// Like `finalize`, but without consuming the builder (the points
+    /// are copied), so more points can be added afterwards and another
+    /// path can be built.
+    pub fn build(&self) -> Path
+    {
+        if self.params.is_empty() {
+            Path::from_points(self.points.clone())
+        } else {
+            let mut path = Path::from_points(self.sorted.clone());
+            path.params = Some(self.params.clone());
+            path
+        }
+    }
+    /// Finalize the path and immediately distribute `n` points along
+    /// it (see `Path::evaluate`). Returns no points at all if less than
+    /// two points were added.
+    pub fn finalize_resampled(self, n: usize) -> Vec<Vec4<f32>>
+    {
+        let path = self.finalize();
+        if path.points.len() < 2 {
+            return Vec::new();
+        }
+        path.sample(n)
+    }
+    /// Like `finalize`, but release any excess capacity of the points
+    /// (and parameters) to minimize memory usage.
+    pub fn finalize_compact(self) -> Path
+    {
+        let mut path = self.finalize();
+        path.points.shrink_to_fit();
+        if let Some(ref mut params) = path.params {
+            params.shrink_to_fit();
+        }
+        path
+    }
+    /// Like `finalize`, but calculate the length (and the cumulative
+    /// lengths used to distribute points) right away instead of on the
+    /// first call to `length`.
+    /// The length can then be read through a shared reference by
+    /// `cached_length`.
+    pub fn finalize_eager(self) -> Path
+    {
+        let mut path = self.finalize();
+        path.length();
+        path
+    }
+}
+
+/// Concatenate paths (in order) into a single path. Where the end of
+/// one path coincides with the start of the next one the duplicate
+/// point is skipped.
+pub fn concat<I>(paths: I) -> Path
+    where I: IntoIterator<Item = Path>
+{
+    let mut points: Vec<Vec4<f32>> = Vec::new();
+    for path in paths {
+        let mut iter = path.points.into_iter();
+        if let Some(first) = iter.next() {
+            if points.last() != Some(&first) {
+                points.push(first);
+            }
+        }
+        points.extend(iter);
+    }
+    Path::from_points(points)
+}
+
+/// Find where two paths come closest to each other. Returns the closest
+/// point on `a`, the closest point on `b`, and their distance.
+pub fn closest_approach(a: &Path, b: &Path) -> (Vec4<f32>, Vec4<f32>, f32)
+{
+    let mut closest: (Vec4<f32>, Vec4<f32>, f32) =
+        (na::zero(), na::zero(), f32::MAX);
+    if a.points.is_empty() || b.points.is_empty() {
+        return closest;
+    }
+    // a single point is treated as a segment without length
+    for i in 0..(a.points.len().max(2) - 1) {
+        let p1 = a.points[i];
+        let q1 = a.points[(i+1).min(a.points.len()-1)];
+        for j in 0..(b.points.len().max(2) - 1) {
+            let p2 = b.points[j];
+            let q2 = b.points[(j+1).min(b.points.len()-1)];
+            let (pa, pb) = closest_segments(p1, q1, p2, q2);
+            let dist = na::norm(&(pb - pa));
+            if dist < closest.2 {
+                closest = (pa, pb, dist);
+            }
+        }
+    }
+    closest
+}
+
+/// Resample both paths to `n` evenly spaced points each, so points
+/// with the same index correspond to each other (e.g. for blending).
+pub fn align(a: &Path, b: &Path, n: usize) -> (Path, Path)
+{
+    let resample = |path: &Path| {
+        if path.points.is_empty() {
+            Path::from_points(Vec::new())
+        } else {
+            Path::from_points(path.sample(n))
+        }
+    };
+    (resample(a), resample(b))
+}
+
+/// Check whether the lengths of two paths differ by less than
+/// `epsilon`.
+pub fn lengths_approx_eq(a: &Path, b: &Path, epsilon: f32) -> bool
+{
+    (a.length_streaming() - b.length_streaming()).abs() < epsilon
+}
+
+/// Directed Hausdorff distance from `a` to `b`: the maximum distance
+/// from any point of `a` to the closest point of `b` (`f32::MAX` if
+/// only `b` is empty).
+pub fn directed_hausdorff(a: &Path, b: &Path) -> f32
+{
+    a.max_deviation_from(b)
+}
+
+/// Similarity of two paths between 0 and 1, derived from their
+/// discrete Frechet distance relative to the larger diagonal of their
+/// bounding boxes (1 for identical paths).
+pub fn frechet_similarity(a: &Path, b: &Path) -> f32
+{
+    let frechet = discrete_frechet(&a.points, &b.points);
+    let diagonal = |path: &Path| {
+        let (min, max) = path.aabb();
+        na::norm(&(max - min))
+    };
+    let scale = diagonal(a).max(diagonal(b));
+    if frechet <= 0.0 {
+        1.0
+    } else if scale <= 0.0 || frechet == f32::MAX {
+        0.0
+    } else {
+        1.0 / (1.0 + frechet / scale)
+    }
+}
+
+/// Distance between the turning functions (tangent angle over
+/// normalized arc length) of two paths, sampled at 256 points. The
+/// mean angle difference is removed first, so the result doesn't
+/// depend on position, scale, or rotation (0 for similar shapes).
+pub fn turning_function_distance(a: &Path, b: &Path) -> f32
+{
+    if a.points.is_empty() || b.points.is_empty() {
+        return f32::MAX;
+    }
+    let n = 256;
+    let fa = a.tangent_angle_profile(n);
+    let fb = b.tangent_angle_profile(n);
+    let mut mean = 0.0;
+    for (pa, pb) in fa.iter().zip(&fb) {
+        mean += (pa.1 - pb.1) / (n as f32);
+    }
+    let mut sum = 0.0;
+    for (pa, pb) in fa.iter().zip(&fb) {
+        let delta = pa.1 - pb.1 - mean;
+        sum += delta * delta / (n as f32);
+    }
+    sum.sqrt()
+}
+
+/// Total length of the parts of the boundary of `a` outside of `b` and
+/// of the boundary of `b` outside of `a` (both treated as closed
+/// polygons within the XY plane), the boundary length of their
+/// symmetric difference. Parts where both boundaries overlap (e.g. a
+/// shared edge) belong to neither of them.
+pub fn symmetric_difference_length_2d(a: &Path, b: &Path) -> f32
+{
+    outside_length_2d(a, b) + outside_length_2d(b, a)
+}
+
+/// Length of the parts of the boundary of the closed polygon `a` which
+/// lie outside of the closed polygon `b` (within the XY plane). Parts
+/// lying on a collinear edge of `b` don't count as outside.
+fn outside_length_2d(a: &Path, b: &Path) -> f32
+{
+    let m = a.points.len();
+    let k = b.points.len();
+    let mut length = 0.0;
+    for i in 0..m {
+        let p = a.points[i];
+        let q = a.points[(i+1) % m];
+        let segment_length = distance_2d(p, q);
+        if segment_length == 0.0 {
+            continue;
+        }
+        // split the segment where it crosses the boundary of b
+        let mut cuts: Vec<f32> = vec![0.0, 1.0];
+        let mut overlaps: Vec<(f32, f32)> = Vec::new();
+        for j in 0..k {
+            let c = b.points[j];
+            let d = b.points[(j+1) % k];
+            let denominator = cross_2d(na::zero(), q - p, d - c);
+            if denominator == 0.0 {
+                if cross_2d(p, q, c) == 0.0 {
+                    // collinear, cut out the overlapping interval
+                    let tc = project_2d(p, q, c);
+                    let td = project_2d(p, q, d);
+                    let start = tc.min(td);
+                    let end = tc.max(td);
+                    if start < end {
+                        cuts.push(start);
+                        cuts.push(end);
+                        overlaps.push((start, end));
+                    }
+                }
+                continue;
+            }
+            let t = cross_2d(na::zero(), c - p, d - c) / denominator;
+            let u = cross_2d(na::zero(), c - p, q - p) / denominator;
+            if t > 0.0 && t < 1.0 && (0.0..=1.0).contains(&u) {
+                cuts.push(t);
+            }
+        }
+        cuts.sort_by(|x, y| x.total_cmp(y));
+        for pair in cuts.windows(2) {
+            let t = 0.5 * (pair[0] + pair[1]);
+            if overlaps.iter().any(|&(start, end)| t > start && t < end) {
+                continue;
+            }
+            let middle = p + (q - p) * t;
+            if k == 0 || b.winding_number_2d(middle) == 0 {
+                length += (pair[1] - pair[0]) * segment_length;
+            }
+        }
+    }
+    length
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    fn xy(x: f32, y: f32) -> Vec4<f32>
+    {
+        Vec4::new(x, y, 0.0, 1.0)
+    }
+
+    fn path_xy(points: &[(f32, f32)]) -> Path
+    {
+        let mut builder = PathBuilder::new();
+        for &(x, y) in points {
+            builder.add_point(xy(x, y));
+        }
+        builder.finalize()
+    }
+
+    fn path_xyz(points: &[(f32, f32, f32)]) -> Path
+    {
+        let mut builder = PathBuilder::new();
+        for &(x, y, z) in points {
+            builder.add_point(Vec4::new(x, y, z, 1.0));
+        }
+        builder.finalize()
+    }
+
+    #[test]
+    fn segment_extents_finds_shortest_and_longest()
+    {
+        let path = path_xy(&[(0.0, 0.0), (1.0, 0.0), (1.0, 3.0), (1.0, 3.5)]);
+        assert_eq!(path.segment_extents(), (0.5, 3.0, 2, 1));
+    }
+
+    #[test]
+    fn evaluate_interior_excludes_both_endpoints()
+    {
+        let path = path_xy(&[(0.0, 0.0), (4.0, 0.0), (10.0, 0.0)]);
+        let points = path.evaluate_interior(3);
+        assert_eq!(points.len(), 3);
+        for (point, x) in points.iter().zip(&[2.5, 5.0, 7.5]) {
+            assert!((point.x - x).abs() < 1e-5);
+        }
+        assert!(points.iter().all(|point| point.x > 0.0 && point.x < 10.0));
+    }
+
+    #[test]
+    fn prepend_moves_start_and_extends_length()
+    {
+        let mut path = path_xy(&[(0.0, 0.0), (4.0, 0.0)]);
+        assert_eq!(path.length(), 4.0);
+        path.prepend(xy(-1.0, 0.0));
+        assert_eq!(path.points()[0], xy(-1.0, 0.0));
+        assert_eq!(path.length(), 5.0);
+    }
+
+    #[test]
+    fn prepend_skips_duplicate_first_point()
+    {
+        let mut path = path_xy(&[(0.0, 0.0), (4.0, 0.0)]);
+        path.prepend(xy(0.0, 0.0));
+        assert_eq!(path.points().len(), 2);
+        assert_eq!(path.length(), 4.0);
+    }
+
+    #[test]
+    fn segment_straightness_of_quarter_circle()
+    {
+        // three points on the unit circle, matched against a fine arc
+        let s = 0.5f32.sqrt();
+        let path = path_xy(&[(1.0, 0.0), (s, s), (0.0, 1.0)]);
+        let mut builder = PathBuilder::new();
+        for i in 0..91 {
+            let angle = (i as f32).to_radians();
+            builder.add_point(xy(angle.cos(), angle.sin()));
+        }
+        let curve = builder.finalize();
+        let ratios = path.segment_straightness(&curve);
+        // chord over arc of a 45 degree sector
+        let expected = 2.0 * (PI / 8.0).sin() / (PI / 4.0);
+        assert_eq!(ratios.len(), 2);
+        assert!(ratios.iter().all(|ratio| (ratio - expected).abs() < 1e-3));
+        assert!((path.segment_straightness(&path)[0] - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn evaluate_rational_pulls_toward_heavy_weight()
+    {
+        let mut uniform = path_xy(&[(0.0, 0.0), (1.0, 0.0), (2.0, 0.0)]);
+        let mut builder = PathBuilder::new();
+        builder.add_point(xy(0.0, 0.0))
+            .add_point(Vec4::new(1.0, 0.0, 0.0, 5.0))
+            .add_point(xy(2.0, 0.0));
+        let weighted = builder.finalize();
+        let rational = uniform.evaluate_rational(5);
+        for (a, b) in rational.iter().zip(&uniform.evaluate(5)) {
+            assert!(na::norm(&(*a - *b)) < 1e-6);
+        }
+        let heavy = weighted.evaluate_rational(5);
+        assert!(heavy[1].x > 0.5 && heavy[3].x < 1.5);
+    }
+
+    #[test]
+    fn evaluate_rational_spacing_ignores_weights()
+    {
+        let mut builder = PathBuilder::new();
+        builder.add_point(xy(0.0, 0.0))
+            .add_point(Vec4::new(1.0, 0.0, 0.0, 4.0))
+            .add_point(xy(2.0, 0.0));
+        let path = builder.finalize();
+        // equal weights would give x = 1/2, heavy middle point pulls
+        let points = path.evaluate_rational(5);
+        assert_eq!(points.len(), 5);
+        assert_eq!(points[0], xy(0.0, 0.0));
+        assert!((points[1].x - 0.8).abs() < 1e-6);
+        assert!((points[2].x - 1.0).abs() < 1e-6);
+        assert_eq!(points[4], xy(2.0, 0.0));
+    }
+
+    #[test]
+    fn evaluate_rational_rejects_non_positive_weights()
+    {
+        let mut builder = PathBuilder::new();
+        builder.add_point(xy(0.0, 0.0))
+            .add_point(Vec4::new(1.0, 0.0, 0.0, 0.0))
+            .add_point(xy(2.0, 0.0));
+        let path = builder.finalize();
+        assert!(path.evaluate_rational(5).is_empty());
+    }
+
+    #[test]
+    fn diameter_of_known_farthest_pair()
+    {
+        let path = path_xy(&[(0.0, 0.0), (3.0, 4.0), (1.0, 1.0), (-3.0, -4.0)]);
+        assert!((path.diameter() - 10.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn finalize_eager_matches_lazy_length()
+    {
+        let mut lazy = PathBuilder::new();
+        let mut eager = PathBuilder::new();
+        for &(x, y) in &[(0.0, 0.0), (3.0, 4.0), (3.0, 7.0)] {
+            lazy.add_point(xy(x, y));
+            eager.add_point(xy(x, y));
+        }
+        let mut lazy = lazy.finalize();
+        let eager = eager.finalize_eager();
+        assert_eq!(lazy.cached_length(), None);
+        assert_eq!(eager.cached_length(), Some(lazy.length()));
+        assert_eq!(lazy.cached_length(), Some(8.0));
+    }
+
+    #[test]
+    fn truncate_to_length_cuts_inside_segment()
+    {
+        let mut path = path_xy(&[(0.0, 0.0), (5.0, 0.0), (10.0, 0.0)]);
+        path.truncate_to_length(6.5);
+        assert_eq!(path.length(), 6.5);
+        assert_eq!(path.points().len(), 3);
+        assert_eq!(path.points()[2], xy(6.5, 0.0));
+    }
+
+    #[test]
+    fn try_finalize_rejects_identical_params()
+    {
+        let mut builder = PathBuilder::new();
+        builder.add_sorted_point(xy(0.0, 0.0), 1.0)
+            .add_sorted_point(xy(1.0, 0.0), 0.0)
+            .add_sorted_point(xy(2.0, 0.0), 1.0);
+        assert_eq!(builder.try_finalize().err(),
+                   Some(PathError::DuplicateParam { index: 2 }));
+    }
+
+    #[test]
+    fn frames_along_diagonal()
+    {
+        let mut path = path_xy(&[(0.0, 0.0), (1.0, 1.0), (3.0, 3.0)]);
+        let frames = path.frames(5);
+        let points = path.evaluate(5);
+        let s = 0.5f32.sqrt();
+        for (&(position, tangent), point) in frames.iter().zip(&points) {
+            assert!(na::norm(&(position - *point)) < 1e-5);
+            assert!(na::norm(&(tangent - Vec4::new(s, s, 0.0, 0.0))) < 1e-6);
+        }
+    }
+
+    #[test]
+    fn to_obj_writes_vertices_and_line()
+    {
+        let path = path_xy(&[(0.0, 0.0), (1.5, 1.0), (3.0, 3.0)]);
+        let obj = path.to_obj();
+        assert_eq!(obj.lines().filter(|line| line.starts_with("v ")).count(),
+                   3);
+        assert_eq!(obj, "v 0 0 0\nv 1.5 1 0\nv 3 3 0\nl 1 2 3\n");
+    }
+
+    #[test]
+    fn resample_by_spacing_capped_honors_cap()
+    {
+        let path = path_xy(&[(0.0, 0.0), (10.0, 0.0)]);
+        assert_eq!(path.resample_by_spacing_capped(3.0, 100).len(), 4);
+        let points = path.resample_by_spacing_capped(0.001, 5);
+        assert_eq!(points.len(), 5);
+        assert_eq!(points[0], xy(0.0, 0.0));
+        assert_eq!(points[4], xy(10.0, 0.0));
+    }
+
+    #[test]
+    fn current_length_grows_with_each_point()
+    {
+        let mut builder = PathBuilder::new();
+        assert_eq!(builder.current_length(), 0.0);
+        builder.add_point(xy(0.0, 0.0));
+        assert_eq!(builder.current_length(), 0.0);
+        builder.add_point(xy(3.0, 4.0));
+        assert_eq!(builder.current_length(), 5.0);
+        builder.add_point(xy(3.0, 6.0));
+        assert_eq!(builder.current_length(), 7.0);
+    }
+
+    #[test]
+    fn rotate_2d_quarter_turn()
+    {
+        let mut path = path_xy(&[(1.0, 0.0), (2.0, 0.0)]);
+        path.rotate_2d(PI / 2.0, xy(0.0, 0.0));
+        assert!(na::norm(&(path.points()[0] - xy(0.0, 1.0))) < 1e-6);
+        assert!(na::norm(&(path.points()[1] - xy(0.0, 2.0))) < 1e-6);
+        assert!((path.length() - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn nearest_vertex_to_distance_picks_nearer_vertex()
+    {
+        let path = path_xy(&[(0.0, 0.0), (1.0, 0.0), (4.0, 0.0), (10.0, 0.0)]);
+        assert_eq!(path.nearest_vertex_to_distance(2.0),
+                   Some((1, xy(1.0, 0.0))));
+        assert_eq!(path.nearest_vertex_to_distance(3.0),
+                   Some((2, xy(4.0, 0.0))));
+        assert_eq!(path_xy(&[]).nearest_vertex_to_distance(3.0), None);
+    }
+
+    #[test]
+    fn mirror_negates_x_and_keeps_length()
+    {
+        let mut path = path_xy(&[(1.0, 0.0), (2.0, 3.0)]);
+        let length = path.length();
+        path.mirror(Axis::X);
+        assert_eq!(path.points(), &[xy(-1.0, 0.0), xy(-2.0, 3.0)][..]);
+        assert_eq!(path.length(), length);
+    }
+
+    #[test]
+    fn sample_distances_are_evenly_spaced()
+    {
+        let path = path_xy(&[(0.0, 0.0), (3.0, 4.0), (3.0, 7.0)]);
+        assert_eq!(path.sample_distances(5), vec![0.0, 2.0, 4.0, 6.0, 8.0]);
+    }
+
+    #[test]
+    fn params_are_returned_sorted()
+    {
+        let mut builder = PathBuilder::new();
+        builder.add_sorted_point(xy(0.0, 0.0), 1.0)
+            .add_sorted_point(xy(1.0, 0.0), 0.0)
+            .add_sorted_point(xy(2.0, 0.0), 3.0);
+        let path = builder.finalize();
+        assert_eq!(path.params(), Some(&[0.0, 1.0, 3.0][..]));
+        assert_eq!(path_xy(&[(0.0, 0.0)]).params(), None);
+    }
+
+    #[test]
+    fn signed_distance_2d_sign_depends_on_side()
+    {
+        let path = path_xy(&[(0.0, 0.0), (5.0, 0.0), (10.0, 0.0)]);
+        assert!((path.signed_distance_2d(xy(3.0, 2.0)) - 2.0).abs() < 1e-6);
+        assert!((path.signed_distance_2d(xy(7.0, -1.5)) + 1.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn concat_sums_lengths()
+    {
+        let a = path_xy(&[(0.0, 0.0), (1.0, 0.0)]);
+        let b = path_xy(&[(1.0, 0.0), (1.0, 2.0)]);
+        let c = path_xy(&[(1.0, 3.0), (0.0, 3.0)]);
+        let mut path = concat(vec![a, b, c]);
+        // the shared point of a and b is only kept once
+        assert_eq!(path.points().len(), 5);
+        assert_eq!(path.length(), 5.0);
+    }
+
+    #[test]
+    fn concat_keeps_repeated_interior_points()
+    {
+        let a = path_xy(&[(0.0, 0.0), (1.0, 0.0), (1.0, 0.0), (2.0, 0.0)]);
+        let b = path_xy(&[(2.0, 0.0), (3.0, 0.0)]);
+        let path = concat(vec![a, b]);
+        assert_eq!(path.points(),
+                   &[xy(0.0, 0.0), xy(1.0, 0.0), xy(1.0, 0.0),
+                     xy(2.0, 0.0), xy(3.0, 0.0)][..]);
+    }
+
+    #[test]
+    fn length_2d_ignores_z()
+    {
+        let mut path = path_xyz(&[(0.0, 0.0, 0.0), (3.0, 4.0, 12.0)]);
+        assert_eq!(path.length_2d(), 5.0);
+        assert_eq!(path.length(), 13.0);
+    }
+
+    #[test]
+    fn finalize_resampled_matches_evaluate()
+    {
+        let points = [(0.0, 0.0), (3.0, 4.0), (3.0, 7.0)];
+        let mut builder = PathBuilder::new();
+        for &(x, y) in &points {
+            builder.add_point(xy(x, y));
+        }
+        let resampled = builder.finalize_resampled(7);
+        let evaluated = path_xy(&points).evaluate(7);
+        assert_eq!(resampled.len(), 7);
+        for (a, b) in resampled.iter().zip(&evaluated) {
+            assert!(na::norm(&(*a - *b)) < 1e-5);
+        }
+    }
+
+    #[test]
+    fn vertex_deviations_flag_off_line_vertex()
+    {
+        let path = path_xy(&[(0.0, 0.0), (1.0, 0.0), (2.0, 3.0), (3.0, 0.0),
+                             (4.0, 0.0)]);
+        let deviations = path.vertex_deviations();
+        assert_eq!(deviations.len(), 3);
+        assert!(deviations[1] > 2.9);
+        let straight = path_xy(&[(0.0, 0.0), (1.0, 0.0), (2.0, 0.0)]);
+        assert!(straight.vertex_deviations()[0] < 1e-6);
+    }
+
+    #[test]
+    fn evaluate_at_params_clamps_to_end()
+    {
+        let path = path_xy(&[(0.0, 0.0), (4.0, 0.0), (4.0, 4.0)]);
+        let points = path.evaluate_at_params(&[0.0, 0.25, 1.0, 2.0]);
+        let end = xy(4.0, 4.0);
+        assert_eq!(points, vec![xy(0.0, 0.0), xy(2.0, 0.0), end, end]);
+    }
+
+    #[test]
+    fn try_finalize_rejects_leading_duplicate()
+    {
+        let mut builder = PathBuilder::new();
+        builder.add_point(xy(0.0, 0.0))
+            .add_point(xy(0.0, 0.0))
+            .add_point(xy(1.0, 0.0));
+        assert_eq!(builder.try_finalize().err(),
+                   Some(PathError::DegenerateBoundary));
+    }
+
+    #[test]
+    fn try_finalize_rejects_trailing_duplicate()
+    {
+        let mut builder = PathBuilder::new();
+        builder.add_point(xy(0.0, 0.0))
+            .add_point(xy(1.0, 0.0))
+            .add_point(xy(1.0, 0.0));
+        assert_eq!(builder.try_finalize().err(),
+                   Some(PathError::DegenerateBoundary));
+    }
+
+    #[test]
+    fn resample_in_place_matches_evaluate()
+    {
+        let mut path = path_xy(&[(0.0, 0.0), (3.0, 4.0), (3.0, 7.0)]);
+        let expected = path.evaluate(9);
+        path.resample_in_place(9);
+        assert_eq!(path.points().len(), 9);
+        for (a, b) in path.points().iter().zip(&expected) {
+            assert!(na::norm(&(*a - *b)) < 1e-5);
+        }
+    }
+
+    #[test]
+    fn average_segment_length_of_mixed_lengths()
+    {
+        let path = path_xy(&[(0.0, 0.0), (1.0, 0.0), (1.0, 3.0), (6.0, 3.0)]);
+        assert_eq!(path.average_segment_length(), 3.0);
+    }
+
+    #[test]
+    fn to_tube_radii_interpolates_w()
+    {
+        let mut builder = PathBuilder::new();
+        builder.add_point(Vec4::new(0.0, 0.0, 0.0, 1.0))
+            .add_point(Vec4::new(4.0, 0.0, 0.0, 3.0));
+        let tube = builder.finalize().to_tube_radii(3);
+        assert_eq!(tube[1].1, 2.0);
+        assert_eq!(tube[1].0.x, 2.0);
+        assert_eq!(tube[1].0.w, 1.0);
+    }
+
+    #[test]
+    fn to_tube_radii_spacing_ignores_radii()
+    {
+        let mut builder = PathBuilder::new();
+        builder.add_point(Vec4::new(0.0, 0.0, 0.0, 1.0))
+            .add_point(Vec4::new(1.0, 0.0, 0.0, 9.0))
+            .add_point(Vec4::new(3.0, 0.0, 0.0, 1.0))
+            .add_point(Vec4::new(4.0, 0.0, 0.0, 5.0));
+        let tube = builder.finalize().to_tube_radii(9);
+        for (i, &(point, _)) in tube.iter().enumerate() {
+            assert!((point.x - 0.5 * (i as f32)).abs() < 1e-5);
+        }
+        assert!((tube[1].1 - 5.0).abs() < 1e-5);
+        assert!((tube[4].1 - 5.0).abs() < 1e-5);
+        assert!((tube[7].1 - 3.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn for_loop_over_path_reference()
+    {
+        let path = path_xy(&[(0.0, 1.0), (1.0, 2.0), (3.0, 4.0)]);
+        let mut sum = 0.0;
+        for point in &path {
+            sum += point.x + point.y;
+        }
+        assert_eq!(sum, 11.0);
+    }
+
+    #[test]
+    fn elevation_gain_and_loss()
+    {
+        // z changes by +3, -1 and +2
+        let path = path_xyz(&[(0.0, 0.0, 0.0), (1.0, 0.0, 3.0), (2.0, 0.0, 2.0),
+                              (3.0, 0.0, 4.0)]);
+        assert_eq!(path.elevation_gain(), 5.0);
+        assert_eq!(path.elevation_loss(), 1.0);
+    }
+
+    #[test]
+    fn length_approx_is_bounded_by_length()
+    {
+        let mut builder = PathBuilder::new();
+        for i in 0..101 {
+            let angle = (i as f32) * PI / 100.0;
+            builder.add_point(xy(angle.cos(), angle.sin()));
+        }
+        let mut path = builder.finalize();
+        let length = path.length();
+        let approx = path.length_approx(2);
+        assert!(approx <= length);
+        assert!((length - approx) / length < 1e-3);
+        assert!((path.length_approx(1) - length).abs() < 1e-6);
+    }
+
+    #[test]
+    fn clamp_to_aabb_pulls_points_back()
+    {
+        let mut path = path_xyz(&[(0.0, 0.0, 0.0), (5.0, -3.0, 1.0),
+                                  (1.0, 1.0, 9.0)]);
+        path.clamp_to_aabb(Vec4::new(-1.0, -1.0, -1.0, 0.0),
+                           Vec4::new(2.0, 2.0, 2.0, 0.0));
+        assert_eq!(path.points()[0], Vec4::new(0.0, 0.0, 0.0, 1.0));
+        assert_eq!(path.points()[1], Vec4::new(2.0, -1.0, 1.0, 1.0));
+        assert_eq!(path.points()[2], Vec4::new(1.0, 1.0, 2.0, 1.0));
+    }
+
+    #[test]
+    fn regular_polygon_square()
+    {
+        let path = PathBuilder::regular_polygon(4, 2.0, xy(1.0, 1.0))
+            .finalize();
+        let expected = [xy(3.0, 1.0), xy(1.0, 3.0),
+                        xy(-1.0, 1.0), xy(1.0, -1.0)];
+        assert_eq!(path.points().len(), 4);
+        for (a, b) in path.points().iter().zip(&expected) {
+            assert!(na::norm(&(*a - *b)) < 1e-6);
+        }
+    }
+
+    #[test]
+    fn bounding_sphere_contains_all_points()
+    {
+        let path = path_xyz(&[(0.0, 0.0, 0.0), (4.0, -2.0, 1.0),
+                              (1.0, 3.0, 2.0)]);
+        let (center, radius) = path.bounding_sphere();
+        assert_eq!(center, Vec4::new(2.0, 0.5, 1.0, 1.0));
+        for point in &path {
+            assert!(na::norm(&(*point - center)) <= radius + 1e-6);
+        }
+    }
+
+    #[test]
+    fn add_point_accepts_tuples()
+    {
+        let mut builder = PathBuilder::new();
+        builder.add_point((1.0, 2.0, 3.0, 4.0)).add_point((5.0, 6.0, 7.0));
+        let path = builder.finalize();
+        assert_eq!(path.points()[0], Vec4::new(1.0, 2.0, 3.0, 4.0));
+        assert_eq!(path.points()[1], Vec4::new(5.0, 6.0, 7.0, 1.0));
+    }
+
+    #[test]
+    fn add_sorted_point_accepts_tuples()
+    {
+        let mut builder = PathBuilder::new();
+        builder.add_sorted_point((5.0, 6.0, 7.0), 1.0)
+            .add_sorted_point((1.0, 2.0, 3.0, 4.0), 0.0);
+        let path = builder.finalize();
+        assert_eq!(path.points()[0], Vec4::new(1.0, 2.0, 3.0, 4.0));
+        assert_eq!(path.points()[1], Vec4::new(5.0, 6.0, 7.0, 1.0));
+    }
+
+    #[test]
+    fn max_deviation_from_simplified_path()
+    {
+        let path = path_xy(&[(0.0, 0.0), (1.0, 0.1), (2.0, -0.05), (3.0, 0.0),
+                             (4.0, 2.0), (5.0, 2.1), (6.0, 2.0)]);
+        // Douglas-Peucker with epsilon 0.2 keeps points 0, 3, 4 and 6
+        let simplified = path_xy(&[(0.0, 0.0), (3.0, 0.0), (4.0, 2.0),
+                                   (6.0, 2.0)]);
+        let deviation = path.max_deviation_from(&simplified);
+        assert!((deviation - 0.1).abs() < 1e-5);
+        assert_eq!(simplified.max_deviation_from(&path), 0.0);
+        assert_eq!(simplified.max_deviation_from(&path_xy(&[])), f32::MAX);
+        assert_eq!(path_xy(&[]).closest_point(xy(1.0, 2.0)), None);
+    }
+
+    #[test]
+    fn locate_mid_segment()
+    {
+        let path = path_xy(&[(0.0, 0.0), (2.0, 0.0), (2.0, 4.0), (0.0, 4.0)]);
+        assert_eq!(path.locate(3.0), (1, 0.25));
+        assert_eq!(path.locate(7.0), (2, 0.5));
+    }
+
+    #[test]
+    fn evaluate_single_point_path()
+    {
+        let mut path = path_xy(&[(1.0, 2.0)]);
+        assert_eq!(path.evaluate(5), vec![xy(1.0, 2.0); 5]);
+    }
+
+    #[test]
+    fn insert_at_projection_splits_middle_segment()
+    {
+        let mut path = path_xy(&[(0.0, 0.0), (2.0, 0.0), (4.0, 0.0),
+                                 (6.0, 0.0)]);
+        path.insert_at_projection(xy(3.0, 1.0));
+        assert_eq!(path.points().len(), 5);
+        assert_eq!(path.points()[2], xy(3.0, 0.0));
+        assert_eq!(path.length(), 6.0);
+    }
+
+    #[test]
+    fn length_streaming_matches_length()
+    {
+        let mut builder = PathBuilder::new();
+        for i in 0..100000 {
+            let a = (i as f32) * 0.001;
+            builder.add_point(xy(a * a.cos(), a.sin()));
+        }
+        let mut path = builder.finalize();
+        let streaming = path.length_streaming();
+        assert_eq!(streaming, path.length());
+    }
+
+    #[test]
+    fn frame_at_distance_past_corner()
+    {
+        let path = path_xy(&[(0.0, 0.0), (2.0, 0.0), (2.0, 4.0)]);
+        let up = Vec4::new(0.0, 1.0, 0.0, 0.0);
+        assert_eq!(path.frame_at_distance(3.0), Some((xy(2.0, 1.0), up)));
+        assert_eq!(path.frame_at_distance(30.0), Some((xy(2.0, 4.0), up)));
+        assert_eq!(path_xy(&[]).frame_at_distance(3.0), None);
+    }
+
+    #[test]
+    fn add_polar_half_circle()
+    {
+        let mut builder = PathBuilder::new();
+        for i in 0..5 {
+            builder.add_polar(2.0, PI * (i as f32) / 4.0);
+        }
+        let path = builder.finalize();
+        assert!(na::norm(&(path.points()[0] - xy(2.0, 0.0))) < 1e-6);
+        assert!(na::norm(&(path.points()[2] - xy(0.0, 2.0))) < 1e-6);
+        assert!(na::norm(&(path.points()[4] - xy(-2.0, 0.0))) < 1e-6);
+    }
+
+    #[test]
+    fn closest_param_of_point_beside_path()
+    {
+        let path = path_xy(&[(0.0, 0.0), (5.0, 0.0), (10.0, 0.0)]);
+        assert!((path.closest_param(xy(7.0, 3.0)) - 0.7).abs() < 1e-6);
+    }
+
+    #[test]
+    fn iter_with_distance_pairs_cumulative_lengths()
+    {
+        let path = path_xy(&[(0.0, 0.0), (3.0, 0.0), (3.0, 4.0)]);
+        let pairs = path.iter_with_distance().collect::<Vec<_>>();
+        assert_eq!(pairs, vec![(0.0, xy(0.0, 0.0)), (3.0, xy(3.0, 0.0)),
+                               (7.0, xy(3.0, 4.0))]);
+    }
+
+    #[test]
+    fn evaluate_catmull_rom_closed_is_periodic()
+    {
+        let path = path_xy(&[(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)]);
+        let points = path.evaluate_catmull_rom_closed(41);
+        assert_eq!(points.len(), 41);
+        assert!(na::norm(&(points[0] - points[40])) < 1e-6);
+        assert!(na::norm(&(points[10] - xy(1.0, 0.0))) < 1e-6);
+        // no kink where the curve closes
+        let first = na::normalize(&(points[1] - points[0]));
+        let last = na::normalize(&(points[40] - points[39]));
+        assert!(na::dot(&first, &last) > 0.9);
+    }
+
+    #[test]
+    fn chunks_by_length_leaves_short_remainder()
+    {
+        let path = path_xy(&[(0.0, 0.0), (4.0, 0.0), (10.0, 0.0)]);
+        let mut chunks = path.chunks_by_length(3.0);
+        let lengths = chunks.iter_mut()
+            .map(|chunk| chunk.length())
+            .collect::<Vec<_>>();
+        assert_eq!(lengths, vec![3.0, 3.0, 3.0, 1.0]);
+        assert_eq!(chunks[1].points(), &[xy(3.0, 0.0), xy(4.0, 0.0),
+                                         xy(6.0, 0.0)][..]);
+    }
+
+    #[test]
+    fn reduction_ratio_after_dropping_points()
+    {
+        let mut path = path_xy(&[(0.0, 0.0), (4.0, 0.0), (10.0, 0.0),
+                                 (11.0, 0.0)]);
+        assert_eq!(path.reduction_ratio(), 1.0);
+        path.truncate_to_length(5.0);
+        assert_eq!(path.reduction_ratio(), 0.75);
+    }
+
+    #[test]
+    fn reduction_ratio_after_resample_in_place()
+    {
+        let mut path = path_xy(&[(0.0, 0.0), (1.0, 0.0), (2.0, 0.0),
+                                 (3.0, 0.0), (4.0, 0.0)]);
+        path.resample_in_place(2);
+        assert_eq!(path.points(), &[xy(0.0, 0.0), xy(4.0, 0.0)][..]);
+        assert_eq!(path.reduction_ratio(), 0.4);
+    }
+
+    #[test]
+    fn with_max_points_reports_overflow()
+    {
+        let mut builder = PathBuilder::with_max_points(3);
+        for i in 0..3 {
+            builder.add_point(xy(i as f32, 0.0));
+        }
+        assert!(!builder.overflowed());
+        builder.add_point(xy(3.0, 0.0)).add_point(xy(4.0, 0.0));
+        assert!(builder.overflowed());
+        assert_eq!(builder.finalize().points().len(), 3);
+    }
+
+    #[test]
+    fn obb_2d_of_rotated_rectangle()
+    {
+        let path = path_xy(&[(0.0, 0.0), (4.0, 4.0), (5.0, 3.0), (1.0, -1.0)]);
+        let (center, half_width, half_height, angle) = path.obb_2d();
+        assert!((angle - PI / 4.0).abs() < 1e-5);
+        assert!((half_width - 8.0f32.sqrt()).abs() < 1e-5);
+        assert!((half_height - 0.5f32.sqrt()).abs() < 1e-5);
+        assert!(na::norm(&(center - xy(2.5, 1.5))) < 1e-5);
+    }
+
+    #[test]
+    fn length_between_non_adjacent_points()
+    {
+        let mut path = path_xy(&[(0.0, 0.0), (3.0, 0.0), (3.0, 4.0),
+                                 (0.0, 4.0)]);
+        assert_eq!(path.length_between(1, 3), 7.0);
+        path.length();
+        assert_eq!(path.length_between(3, 0), 10.0);
+    }
+
+    #[test]
+    fn resample_length_preserving_keeps_length()
+    {
+        let mut path = path_xy(&[(0.0, 0.0), (3.0, 0.3), (3.0, 4.0),
+                                 (0.1, 4.0)]);
+        let length = path.length();
+        let mut resampled = path.resample_length_preserving(7);
+        assert!((resampled.length() - length).abs() < 1e-5);
+        // the corners are kept in addition to the 7 evenly spaced points
+        assert_eq!(resampled.points().len(), 9);
+    }
+
+    #[test]
+    fn resample_length_preserving_survives_nan_point()
+    {
+        let path = path_xy(&[(0.0, 0.0), (1.0, 0.0), (f32::NAN, 0.0),
+                             (3.0, 0.0)]);
+        let resampled = path.resample_length_preserving(3);
+        assert_eq!(resampled.points()[0], xy(0.0, 0.0));
+        assert_eq!(resampled.points()[1], xy(1.0, 0.0));
+    }
+
+    #[test]
+    fn angle_to_at_quarter_turn()
+    {
+        let path = path_xy(&[(0.0, 0.0), (3.0, 0.0), (3.0, 3.0)]);
+        let up = Vec4::new(0.0, 1.0, 0.0, 0.0);
+        assert!((path.angle_to_at(0.2, up) - PI / 2.0).abs() < 1e-6);
+        assert!(path.angle_to_at(0.9, up).abs() < 1e-6);
+    }
+
+    #[test]
+    fn build_keeps_builder_usable()
+    {
+        let mut builder = PathBuilder::new();
+        builder.add_point(xy(0.0, 0.0)).add_point(xy(1.0, 0.0));
+        let mut first = builder.build();
+        builder.add_point(xy(3.0, 0.0));
+        let mut second = builder.build();
+        assert_eq!(first.length(), 1.0);
+        assert_eq!(second.length(), 3.0);
+        assert_eq!(builder.finalize().points().len(), 3);
+    }
+
+    #[test]
+    fn curvature_density_clusters_at_corner()
+    {
+        let path = path_xy(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)]);
+        let points = path.curvature_density(1.0, 50.0);
+        let near = |center: Vec4<f32>| {
+            points.iter()
+                .filter(|point| na::norm(&(**point - center)) < 2.0)
+                .count()
+        };
+        assert!(near(xy(10.0, 0.0)) > 2 * near(xy(3.0, 0.0)));
+        assert_eq!(points[points.len()-1], xy(10.0, 10.0));
+    }
+
+    #[test]
+    fn add_point_dedup_skips_near_duplicate()
+    {
+        let mut builder = PathBuilder::new();
+        builder.add_point_dedup(xy(0.0, 0.0), 0.01)
+            .add_point_dedup(xy(0.005, 0.0), 0.01)
+            .add_point_dedup(xy(1.0, 0.0), 0.01);
+        let path = builder.finalize();
+        assert_eq!(path.points(), &[xy(0.0, 0.0), xy(1.0, 0.0)][..]);
+    }
+
+    #[test]
+    fn rmf_frames_of_planar_path_stay_in_plane()
+    {
+        let path = path_xy(&[(0.0, 0.0), (1.0, 0.0), (2.0, 1.0), (2.0, 3.0),
+                             (0.0, 4.0)]);
+        let frames = path.rmf_frames(30);
+        let side = |t: Vec4<f32>, n: Vec4<f32>| t.x * n.y - t.y * n.x;
+        let first = side(frames[0].1, frames[0].2);
+        for &(_point, tangent, normal) in &frames {
+            assert!(normal.z.abs() < 1e-5);
+            assert!((na::norm(&normal) - 1.0).abs() < 1e-4);
+            assert!(na::dot(&tangent, &normal).abs() < 1e-4);
+            // the normal never flips to the other side of the path
+            assert!((side(tangent, normal) - first).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn closest_approach_of_crossing_paths()
+    {
+        let a = path_xy(&[(0.0, 0.0), (4.0, 4.0)]);
+        let b = path_xy(&[(0.0, 4.0), (1.0, 3.0), (4.0, 0.0)]);
+        let (on_a, on_b, distance) = closest_approach(&a, &b);
+        assert!(distance < 1e-5);
+        assert!(na::norm(&(on_a - xy(2.0, 2.0))) < 1e-5);
+        assert!(na::norm(&(on_b - xy(2.0, 2.0))) < 1e-5);
+    }
+
+    #[test]
+    fn convex_hull_2d_of_quadrilateral()
+    {
+        let path = path_xy(&[(0.0, 0.0), (1.0, 1.0), (4.0, 0.0), (2.0, 0.0),
+                             (3.0, 1.0), (4.0, 3.0), (2.0, 2.0), (0.0, 3.0),
+                             (f32::NAN, 1.0)]);
+        assert_eq!(path.convex_hull_2d(), vec![xy(0.0, 0.0), xy(4.0, 0.0),
+                                               xy(4.0, 3.0), xy(0.0, 3.0)]);
+    }
+
+    #[test]
+    fn equalize_spacing_gives_equal_segments()
+    {
+        let path = path_xy(&[(0.0, 0.0), (1.0, 0.0), (5.0, 0.0), (6.0, 0.0)]);
+        let equalized = path.equalize_spacing();
+        assert_eq!(equalized.points().len(), 4);
+        for pair in equalized.points().windows(2) {
+            assert!((na::norm(&(pair[1] - pair[0])) - 2.0).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn signed_turn_angles_2d_of_ccw_square()
+    {
+        let path = path_xy(&[(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0),
+                             (0.0, 0.0)]);
+        let angles = path.signed_turn_angles_2d();
+        assert_eq!(angles.len(), 4);
+        assert!(angles.iter().all(|angle| *angle > 0.0));
+        assert!((angles.iter().sum::<f32>() - 2.0 * PI).abs() < 1e-4);
+    }
+
+    #[test]
+    fn evaluate_with_smoothstep_differs_from_linear()
+    {
+        let path = path_xy(&[(0.0, 0.0), (4.0, 0.0)]);
+        let linear = path.evaluate_with(5, |a, b, t| a * (1.0 - t) + b * t);
+        let smooth = path.evaluate_with(5, |a, b, t| {
+            let s = t * t * (3.0 - 2.0 * t);
+            a * (1.0 - s) + b * s
+        });
+        // both agree at t = 0.5 but not at t = 0.25
+        assert!((linear[2].x - smooth[2].x).abs() < 1e-5);
+        assert!((linear[1].x - smooth[1].x).abs() > 0.1);
+    }
+
+    #[test]
+    fn derivatives_of_straight_path()
+    {
+        let path = path_xy(&[(0.0, 0.0), (4.0, 0.0)]);
+        for (first, second) in path.derivatives(5) {
+            assert!((first.x - 4.0).abs() < 1e-4);
+            assert!(na::norm(&second) < 1e-3);
+        }
+    }
+
+    #[test]
+    fn derivatives_of_curved_path()
+    {
+        let path = path_xy(&[(0.0, 0.0), (2.0, 0.0), (2.0, 2.0)]);
+        let derivatives = path.derivatives(5);
+        assert!(na::norm(&derivatives[2].1) > 1.0);
+    }
+
+    #[test]
+    fn derivatives_use_one_sided_differences_at_ends()
+    {
+        // five samples which coincide with the (evenly spaced) points
+        let path = path_xy(&[(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (2.0, 1.0),
+                             (2.0, 2.0)]);
+        let derivatives = path.derivatives(5);
+        let expected = [(-48.0, 48.0), (-16.0, 16.0), (16.0, -16.0),
+                        (-16.0, 16.0), (-48.0, 48.0)];
+        for (&(_, second), &(x, y)) in derivatives.iter().zip(&expected) {
+            assert!((second.x - x).abs() < 1e-3);
+            assert!((second.y - y).abs() < 1e-3);
+            assert_eq!(second.w, 0.0);
+        }
+    }
+
+    #[test]
+    fn align_resamples_both_paths()
+    {
+        let a = path_xy(&[(0.0, 0.0), (1.0, 0.0), (2.0, 0.0)]);
+        let b = path_xy(&[(0.0, 1.0), (1.0, 1.0), (2.0, 1.0), (3.0, 1.0),
+                          (4.0, 1.0), (5.0, 1.0), (6.0, 1.0)]);
+        let (a, b) = align(&a, &b, 10);
+        assert_eq!(a.points().len(), 10);
+        assert_eq!(b.points().len(), 10);
+        assert_eq!(a.points()[9], xy(2.0, 0.0));
+        assert_eq!(b.points()[9], xy(6.0, 1.0));
+    }
+
+    #[test]
+    fn total_elevation_change_sums_gain_and_loss()
+    {
+        let path = path_xyz(&[(0.0, 0.0, 0.0), (1.0, 0.0, 3.0), (2.0, 0.0, 2.0),
+                              (3.0, 0.0, 4.0)]);
+        assert!((path.total_elevation_change() - 6.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn chord_aligned_bounds_of_bowed_path()
+    {
+        let path = path_xy(&[(0.0, 0.0), (1.0, 0.5), (2.0, 1.5), (3.0, 0.5),
+                             (4.0, 0.0)]);
+        let (along, across) = path.chord_aligned_bounds();
+        assert!((along - 4.0).abs() < 1e-5);
+        assert!((across - 1.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn collapse_short_segments_merges_tiny_segments()
+    {
+        let path = path_xy(&[(0.0, 0.0), (0.01, 0.0), (1.0, 0.0), (1.0, 0.02),
+                             (1.01, 1.0), (2.0, 1.0), (2.0, 1.005)]);
+        let collapsed = path.collapse_short_segments(0.1);
+        assert_eq!(collapsed.points().len(), 4);
+        assert_eq!(collapsed.points()[0], xy(0.0, 0.0));
+        assert_eq!(collapsed.points()[3], xy(2.0, 1.005));
+        for pair in collapsed.points().windows(2) {
+            assert!(na::norm(&(pair[1] - pair[0])) >= 0.1);
+        }
+    }
+
+    #[test]
+    fn collapse_short_segments_merges_into_end_point()
+    {
+        // the end point lies within `min_len` of the last kept point
+        let path = path_xy(&[(0.0, 0.0), (1.0, 0.0), (1.15, 0.0),
+                             (1.05, 0.0)]);
+        let collapsed = path.collapse_short_segments(0.2);
+        assert_eq!(collapsed.points(), &[xy(0.0, 0.0), xy(1.05, 0.0)][..]);
+    }
+
+    #[test]
+    fn line_indices_of_open_path()
+    {
+        let path = path_xy(&[(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)]);
+        assert_eq!(path.line_indices(), vec![[0, 1], [1, 2], [2, 3]]);
+    }
+
+    #[test]
+    fn line_indices_of_closed_path()
+    {
+        let path = path_xy(&[(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)]);
+        assert_eq!(path.line_indices_closed(),
+                   vec![[0, 1], [1, 2], [2, 3], [3, 0]]);
+    }
+
+    #[test]
+    fn sharpest_corner_of_l_shape()
+    {
+        let path = path_xy(&[(0.0, 0.0), (1.0, 0.0), (2.0, 0.0), (2.0, 1.0),
+                             (2.0, 2.0)]);
+        let (index, curvature) = path.sharpest_corner().unwrap();
+        assert_eq!(index, 2);
+        assert!(curvature > 1.0);
+        assert_eq!(path_xy(&[(0.0, 0.0), (1.0, 0.0)]).sharpest_corner(), None);
+    }
+
+    #[test]
+    fn try_finalize_rejects_mixed_build_modes()
+    {
+        let mut builder = PathBuilder::new();
+        builder.add_point(xy(0.0, 0.0)).add_sorted_point(xy(1.0, 0.0), 0.5);
+        assert_eq!(builder.try_finalize().err(),
+                   Some(PathError::MixedBuildModes));
+    }
+
+    #[test]
+    fn catmull_rom_length_converges()
+    {
+        let path = path_xy(&[(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (2.0, 1.0)]);
+        let coarse = path.catmull_rom_length(4);
+        let fine = path.catmull_rom_length(64);
+        let finer = path.catmull_rom_length(256);
+        assert!(fine > path.length_streaming());
+        assert!((finer - fine).abs() < (fine - coarse).abs());
+        assert!((finer - fine).abs() < 1e-3);
+    }
+
+    #[test]
+    fn rebase_to_origin_keeps_relative_positions()
+    {
+        let mut path = path_xyz(&[(1.0, 2.0, 3.0), (2.0, 2.0, 3.0),
+                                  (2.0, 5.0, 4.0)]);
+        let length = path.length();
+        path.rebase_to_origin();
+        assert_eq!(path.points()[0], Vec4::new(0.0, 0.0, 0.0, 1.0));
+        assert_eq!(path.points()[1], Vec4::new(1.0, 0.0, 0.0, 1.0));
+        assert_eq!(path.points()[2], Vec4::new(1.0, 3.0, 1.0, 1.0));
+        assert!((path.length() - length).abs() < 1e-6);
+    }
+
+    #[test]
+    fn resample_by_density_of_ten_units()
+    {
+        let path = path_xy(&[(0.0, 0.0), (4.0, 0.0), (10.0, 0.0)]);
+        let points = path.resample_by_density(2.0);
+        assert_eq!(points.len(), 21);
+        assert_eq!(points[20], xy(10.0, 0.0));
+    }
+
+    #[test]
+    fn resample_by_density_rejects_non_finite_density()
+    {
+        let path = path_xy(&[(0.0, 0.0), (10.0, 0.0)]);
+        assert!(path.resample_by_density(f32::INFINITY).is_empty());
+        assert!(path.resample_by_density(f32::NAN).is_empty());
+        assert_eq!(path.resample_by_density(0.0).len(), 1);
+    }
+
+    #[test]
+    fn to_param_table_keys_increase()
+    {
+        let path = path_xy(&[(0.0, 0.0), (3.0, 0.0), (3.0, 4.0)]);
+        let table = path.to_param_table(8);
+        assert_eq!(table.len(), 8);
+        assert_eq!(table[0].0, 0.0);
+        assert!((table[7].0 - 7.0).abs() < 1e-5);
+        assert!(table.windows(2).all(|pair| pair[1].0 > pair[0].0));
+    }
+
+    #[test]
+    fn lengths_approx_eq_of_resampled_copy()
+    {
+        let path = path_xy(&[(0.0, 0.0), (3.0, 0.0), (3.0, 4.0)]);
+        let copy = path.resample_length_preserving(9);
+        assert!(lengths_approx_eq(&path, &copy, 1e-4));
+        let chord = path_xy(&[(0.0, 0.0), (3.0, 4.0)]);
+        assert!(!lengths_approx_eq(&path, &chord, 1e-4));
+    }
+
+    #[test]
+    fn extent_along_diagonal_matches_chord()
+    {
+        let path = path_xy(&[(1.0, 1.0), (2.0, 2.5), (4.0, 5.0)]);
+        let (min, max) = path.extent_along(xy(4.0, 5.0) - xy(1.0, 1.0));
+        assert!((max - min - 5.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn extent_along_ignores_w_of_axis()
+    {
+        let path = path_xy(&[(1.0, 0.0), (3.0, 0.0)]);
+        assert_eq!(path.extent_along(xy(2.0, 0.0)), (1.0, 3.0));
+    }
+
+    #[test]
+    fn reverse_range_reorders_inner_points()
+    {
+        let mut builder = PathBuilder::new();
+        for i in 0..5 {
+            builder.add_sorted_point(xy(i as f32, 0.0), i as f32);
+        }
+        let mut path = builder.finalize();
+        path.reverse_range(1, 3);
+        let xs = path.points().iter().map(|point| point.x).collect::<Vec<_>>();
+        assert_eq!(xs, vec![0.0, 3.0, 2.0, 1.0, 4.0]);
+        assert_eq!(path.params(), None);
+        assert!((path.length() - 8.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn turning_number_of_pentagon()
+    {
+        let mut points = Vec::new();
+        for i in 0..5 {
+            let angle = (i as f32) * 2.0 * PI / 5.0;
+            points.push((angle.cos(), angle.sin()));
+        }
+        assert_eq!(path_xy(&points).turning_number(), 1);
+        points.reverse();
+        assert_eq!(path_xy(&points).turning_number(), -1);
+    }
+
+    #[test]
+    fn turning_number_of_figure_eight()
+    {
+        let path = path_xy(&[(0.0, 0.0), (1.0, 1.0), (2.0, 0.0), (1.0, -1.0),
+                             (0.0, 0.0), (-1.0, 1.0), (-2.0, 0.0),
+                             (-1.0, -1.0), (0.0, 0.0)]);
+        assert_eq!(path.turning_number(), 0);
+    }
+
+    #[test]
+    fn catmull_rom_speed_straight_versus_curved()
+    {
+        let straight = path_xy(&[(0.0, 0.0), (1.0, 0.0), (2.0, 0.0),
+                                 (3.0, 0.0), (4.0, 0.0)]);
+        let speeds = straight.catmull_rom_speed(17);
+        assert!(speeds[4..13].iter().all(|speed| (speed - 1.0).abs() < 1e-4));
+        let curved = path_xy(&[(0.0, 0.0), (1.0, 0.0), (1.2, 0.2), (1.2, 3.0),
+                               (4.0, 3.0)]);
+        let speeds = curved.catmull_rom_speed(17);
+        let min = speeds[4..13].iter().cloned().fold(f32::MAX, f32::min);
+        let max = speeds[4..13].iter().cloned().fold(0.0, f32::max);
+        assert!(max - min > 0.5);
+    }
+
+    #[test]
+    fn from_stations_accepts_consistent_stations()
+    {
+        let stations = [(100.0, xy(0.0, 0.0)), (103.0, xy(3.0, 0.0)),
+                        (108.0, xy(3.0, 5.0))];
+        let builder = PathBuilder::from_stations(&stations).unwrap();
+        assert_eq!(builder.finalize().points().len(), 3);
+    }
+
+    #[test]
+    fn from_stations_rejects_inconsistent_stations()
+    {
+        let stations = [(0.0, xy(0.0, 0.0)), (3.0, xy(3.0, 0.0)),
+                        (9.0, xy(3.0, 5.0))];
+        assert_eq!(PathBuilder::from_stations(&stations).err(),
+                   Some(PathError::InvalidStation { index: 2 }));
+    }
+
+    #[test]
+    fn progress_distance_beside_corner()
+    {
+        let path = path_xy(&[(0.0, 0.0), (5.0, 0.0), (5.0, 5.0)]);
+        assert!((path.progress_distance(xy(6.0, 1.0)) - 6.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn join_nearest_reverses_fragment()
+    {
+        let mut path = path_xy(&[(0.0, 0.0), (1.0, 0.0)]);
+        path.join_nearest(&path_xy(&[(3.0, 0.0), (2.0, 0.0), (1.0, 0.0)]));
+        let xs = path.points().iter().map(|point| point.x).collect::<Vec<_>>();
+        assert_eq!(xs, vec![0.0, 1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn fraction_within_half_of_path()
+    {
+        let path = path_xy(&[(0.0, 0.0), (5.0, 0.0), (10.0, 0.0)]);
+        assert!((path.fraction_within(xy(0.0, 0.0), 5.0) - 0.5).abs() < 1e-5);
+        assert_eq!(path.fraction_within(xy(5.0, 30.0), 5.0), 0.0);
+    }
+
+    #[test]
+    fn smooth_tangent_at_is_continuous_across_corner()
+    {
+        let path = path_xy(&[(0.0, 0.0), (1.0, 0.0), (1.0, 1.0)]);
+        let mut previous = path.smooth_tangent_at(0.0);
+        for i in 1..201 {
+            let tangent = path.smooth_tangent_at((i as f32) / 200.0);
+            assert!(na::norm(&(tangent - previous)) < 0.05);
+            previous = tangent;
+        }
+        // at the corner it points halfway between both segments
+        let corner = path.smooth_tangent_at(0.5);
+        assert!((corner.x - corner.y).abs() < 1e-5 && corner.x > 0.0);
+    }
+
+    #[test]
+    fn min_enclosing_circle_2d_of_obtuse_triangle()
+    {
+        // the longest side is the diameter, the third point lies inside
+        let path = path_xy(&[(0.0, 0.0), (4.0, 0.0), (2.0, 0.5)]);
+        let (center, radius) = path.min_enclosing_circle_2d();
+        assert!(na::norm(&(center - xy(2.0, 0.0))) < 1e-5);
+        assert!((radius - 2.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn evaluate_with_segments_indices_increase()
+    {
+        let path = path_xy(&[(0.0, 0.0), (1.0, 0.0), (3.0, 0.0), (6.0, 0.0)]);
+        let samples = path.evaluate_with_segments(13);
+        assert!(samples.windows(2).all(|pair| pair[1].1 >= pair[0].1));
+        assert_eq!(samples[0].1, 0);
+        assert_eq!(samples[4].1, 1);
+        assert_eq!(samples[12], (xy(6.0, 0.0), 2));
+    }
+
+    #[test]
+    fn aspect_ratio_2d_of_four_by_one()
+    {
+        let wide = path_xy(&[(0.0, 0.0), (4.0, 1.0), (2.0, 0.5)]);
+        assert!((wide.aspect_ratio_2d() - 4.0).abs() < 1e-6);
+        let tall = path_xy(&[(0.0, 0.0), (1.0, 4.0)]);
+        assert!((tall.aspect_ratio_2d() - 4.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn simplify_to_count_reaches_target()
+    {
+        let mut points = Vec::new();
+        for i in 0..100 {
+            let x = (i as f32) * 0.1;
+            points.push((x, x.sin()));
+        }
+        let path = path_xy(&points);
+        let simplified = path.simplify_to_count(10);
+        let count = simplified.points().len();
+        assert!((6..=10).contains(&count));
+        assert_eq!(simplified.points()[0], path.points()[0]);
+        assert_eq!(simplified.points()[count-1], path.points()[99]);
+    }
+
+    #[test]
+    fn directed_hausdorff_is_asymmetric()
+    {
+        let peak = path_xy(&[(0.0, 0.0), (1.0, 3.0), (2.0, 0.0)]);
+        let base = path_xy(&[(0.0, 0.0), (2.0, 0.0)]);
+        assert!((directed_hausdorff(&peak, &base) - 3.0).abs() < 1e-6);
+        assert!(directed_hausdorff(&base, &peak) < 1e-6);
+    }
+
+    #[test]
+    fn resample_clamped_spacing_keeps_gaps_within_bounds()
+    {
+        let path = path_xy(&[(0.0, 0.0), (7.0, 0.0), (7.05, 0.0), (7.05, 3.0)]);
+        let resampled = path.resample_clamped_spacing(0.5, 1.0);
+        let points = resampled.points();
+        for pair in points.windows(2) {
+            let gap = path.progress_distance(pair[1]) -
+                path.progress_distance(pair[0]);
+            assert!((0.5 - 1e-4..=1.0 + 1e-4).contains(&gap));
+        }
+        assert_eq!(points[points.len()-1], xy(7.05, 3.0));
+    }
+
+    #[test]
+    fn length_weighted_centroid_of_l_shape()
+    {
+        let path = path_xy(&[(0.0, 0.0), (4.0, 0.0), (4.0, 2.0)]);
+        let centroid = path.length_weighted_centroid();
+        // segment midpoints (2, 0) and (4, 1) weighted by 4 and 2
+        assert!(na::norm(&(centroid - xy(8.0 / 3.0, 1.0 / 3.0))) < 1e-5);
+        // the average of the vertices is (8/3, 2/3)
+        assert!((centroid.y - 2.0 / 3.0).abs() > 0.1);
+    }
+
+    #[test]
+    fn helix_length_matches_analytic_length()
+    {
+        let mut path = PathBuilder::helix(1.0, 2.0, 3.0, 2000).finalize();
+        let exact = ((2.0 * PI * 2.0f32).powi(2) + 9.0).sqrt();
+        assert!((path.length() - exact).abs() < 1e-3 * exact);
+        assert!((path.points()[1999].z - 3.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn evaluate_normalized_2d_maps_corners()
+    {
+        let path = path_xy(&[(-1.0, 2.0), (3.0, 2.0), (3.0, 4.0)]);
+        let coords = path.evaluate_normalized_2d(5);
+        assert_eq!(coords[0], (0.0, 0.0));
+        assert_eq!(coords[4], (1.0, 1.0));
+    }
+
+    #[test]
+    fn intersect_line_2d_of_zig_zag()
+    {
+        let path = path_xy(&[(0.0, 0.0), (1.0, 2.0), (2.0, 0.0), (3.0, 2.0),
+                             (4.0, 1.0)]);
+        let crossings = path.intersect_line_2d(xy(0.0, 1.0), xy(1.0, 1.0));
+        assert_eq!(crossings.len(), 4);
+        for (point, x) in crossings.iter().zip(&[0.5, 1.5, 2.5, 4.0]) {
+            assert!((point.x - x).abs() < 1e-5 && (point.y - 1.0).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn intersect_line_2d_ignores_touching_point()
+    {
+        let (a, b) = (xy(0.0, 1.0), xy(1.0, 1.0));
+        let above = path_xy(&[(0.0, 2.0), (1.0, 1.0), (2.0, 2.0)]);
+        assert!(above.intersect_line_2d(a, b).is_empty());
+        let below = path_xy(&[(0.0, 0.0), (1.0, 1.0), (2.0, 0.0)]);
+        assert!(below.intersect_line_2d(a, b).is_empty());
+        let through = path_xy(&[(0.0, 2.0), (1.0, 1.0), (2.0, 0.0)]);
+        assert_eq!(through.intersect_line_2d(a, b), vec![xy(1.0, 1.0)]);
+    }
+
+    #[test]
+    fn winding_number_2d_of_doubly_wound_square()
+    {
+        let square = [(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)];
+        let mut points = square.to_vec();
+        points.extend_from_slice(&square);
+        let path = path_xy(&points);
+        assert_eq!(path.winding_number_2d(xy(1.0, 1.0)), 2);
+        assert_eq!(path.winding_number_2d(xy(3.0, 1.0)), 0);
+    }
+
+    #[test]
+    fn length_in_aabb_clips_exiting_path()
+    {
+        let path = path_xy(&[(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)]);
+        let min = Vec4::new(-1.0, -1.0, -1.0, 0.0);
+        let max = Vec4::new(2.0, 5.0, 1.0, 0.0);
+        assert!((path.length_in_aabb(min, max) - 4.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn reverse_params_swaps_start_and_end()
+    {
+        let mut builder = PathBuilder::new();
+        builder.add_sorted_point(xy(0.0, 0.0), 0.0)
+            .add_sorted_point(xy(5.0, 0.0), 2.0)
+            .add_sorted_point(xy(5.0, 5.0), 3.0);
+        let mut path = builder.finalize();
+        let before = path.evaluate_at_params(&[0.0, 1.0]);
+        path.reverse_params();
+        let after = path.evaluate_at_params(&[0.0, 1.0]);
+        assert_eq!((before[0], before[1]), (after[1], after[0]));
+        assert_eq!(path.params(), Some(&[0.0, 1.0, 3.0][..]));
+        assert_eq!(path.points()[0], xy(5.0, 5.0));
+    }
+
+    #[test]
+    fn kink_energy_straight_versus_corner()
+    {
+        let straight = path_xy(&[(0.0, 0.0), (1.0, 0.0), (3.0, 0.0)]);
+        assert!(straight.kink_energy().abs() < 1e-6);
+        let corner = path_xy(&[(0.0, 0.0), (1.0, 0.0), (1.0, 1.0)]);
+        assert!(corner.kink_energy() > 0.0);
+    }
+
+    #[test]
+    fn closed_metrics_2d_of_unit_square()
+    {
+        let path = path_xy(&[(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)]);
+        let (perimeter, area) = path.closed_metrics_2d();
+        assert!((perimeter - 4.0).abs() < 1e-6);
+        assert!((area - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn rails_2d_are_offset_by_half_gauge()
+    {
+        let (left, right) = path_xy(&[(0.0, 0.0), (4.0, 0.0)]).rails_2d(0.5, 5);
+        assert_eq!((left.len(), right.len()), (5, 5));
+        for i in 0..5 {
+            assert!((left[i].x - i as f32).abs() < 1e-5);
+            assert!((left[i].y - 0.5).abs() < 1e-6);
+            assert!((right[i].y + 0.5).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn samples_for_chord_error_grows_for_smaller_error()
+    {
+        let path = path_xy(&[(0.0, 0.0), (5.0, 0.0), (5.0, 5.0)]);
+        let coarse = path.samples_for_chord_error(0.1);
+        let fine = path.samples_for_chord_error(0.01);
+        assert!(coarse > 2 && fine > coarse);
+        assert_eq!(path.samples_for_chord_error(0.0), usize::MAX);
+        assert_eq!(path.samples_for_chord_error(-1.0), usize::MAX);
+    }
+
+    #[test]
+    fn moment_of_inertia_2d_of_thin_rod()
+    {
+        // a rod of length 3 about its end is L^3 / 3
+        let path = path_xy(&[(1.0, 0.0), (2.0, 0.0), (4.0, 0.0)]);
+        assert!((path.moment_of_inertia_2d(xy(1.0, 0.0)) - 9.0).abs() < 1e-4);
+        // parallel axis 2 away from its center: L^3 / 12 + L d^2
+        let expected = 27.0 / 12.0 + 3.0 * 4.0;
+        assert!((path.moment_of_inertia_2d(xy(2.5, 2.0)) - expected).abs() <
+                1e-4);
+    }
+
+    #[test]
+    fn is_monotonic_along_increasing_x()
+    {
+        let path = path_xy(&[(0.0, 0.0), (1.0, 2.0), (1.0, 1.0), (3.0, 5.0)]);
+        assert!(path.is_monotonic_along(0));
+    }
+
+    #[test]
+    fn is_monotonic_along_dipping_y()
+    {
+        let path = path_xy(&[(0.0, 0.0), (1.0, 2.0), (1.0, 1.0), (3.0, 5.0)]);
+        assert!(!path.is_monotonic_along(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid axis 3")]
+    fn is_monotonic_along_rejects_invalid_axis()
+    {
+        path_xy(&[(0.0, 0.0), (1.0, 0.0)]).is_monotonic_along(3);
+    }
+
+    #[test]
+    fn lateral_acceleration_is_zero_when_straight()
+    {
+        let path = path_xy(&[(0.0, 0.0), (2.0, 0.0), (4.0, 0.0)]);
+        let accelerations = path.lateral_acceleration(3.0, 9);
+        assert!(accelerations.iter().all(|a| a.abs() < 1e-6));
+    }
+
+    #[test]
+    fn lateral_acceleration_spikes_at_corner()
+    {
+        let path = path_xy(&[(0.0, 0.0), (2.0, 0.0), (2.0, 2.0)]);
+        let accelerations = path.lateral_acceleration(3.0, 9);
+        let max = accelerations.iter().cloned().fold(0.0, f32::max);
+        assert_eq!(accelerations[4], max);
+        assert!(max > 1.0);
+        assert_eq!(accelerations[0], 0.0);
+    }
+
+    #[test]
+    fn length_over_diagonal_straight_versus_zig_zag()
+    {
+        let diagonal = path_xy(&[(0.0, 0.0), (1.0, 1.0), (3.0, 3.0)]);
+        assert!((diagonal.length_over_diagonal() - 1.0).abs() < 1e-5);
+        let zig_zag = path_xy(&[(0.0, 0.0), (1.0, 3.0), (2.0, 0.0),
+                                (3.0, 3.0)]);
+        assert!(zig_zag.length_over_diagonal() > 2.0);
+    }
+
+    #[test]
+    fn evaluate_range_between_quarters()
+    {
+        let path = path_xy(&[(0.0, 0.0), (3.0, 0.0), (8.0, 0.0)]);
+        let points = path.evaluate_range(0.25, 0.75, 5);
+        assert_eq!(points.len(), 5);
+        for (point, x) in points.iter().zip(&[2.0, 3.0, 4.0, 5.0, 6.0]) {
+            assert!((point.x - x).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn distance_to_aabb_clearance()
+    {
+        let min = Vec4::new(0.0, 0.0, 0.0, 0.0);
+        let max = Vec4::new(1.0, 1.0, 1.0, 0.0);
+        // the line x + y = 3 passes (1, 1) at a distance of sqrt(1/2)
+        let path = path_xy(&[(-1.0, 4.0), (4.0, -1.0)]);
+        let distance = path.distance_to_aabb(min, max);
+        assert!((distance - 0.5f32.sqrt()).abs() < 1e-4);
+        let touching = path_xy(&[(-1.0, 3.0), (4.0, -2.0)]);
+        assert!(touching.distance_to_aabb(min, max) < 1e-5);
+    }
+
+    #[test]
+    fn vertices_within_selects_subset()
+    {
+        let path = path_xy(&[(0.0, 0.0), (1.0, 0.0), (5.0, 0.0), (1.0, 1.0),
+                             (0.5, 0.5)]);
+        let within = path.vertices_within(xy(0.0, 0.0), 1.2);
+        let indices = within.iter().map(|&(i, _)| i).collect::<Vec<_>>();
+        assert_eq!(indices, vec![0, 1, 4]);
+    }
+
+    #[test]
+    fn frechet_similarity_identical_versus_separated()
+    {
+        let a = path_xy(&[(0.0, 0.0), (1.0, 0.0), (2.0, 1.0)]);
+        let b = path_xy(&[(0.0, 0.0), (1.0, 0.0), (2.0, 1.0)]);
+        assert_eq!(frechet_similarity(&a, &b), 1.0);
+        let far = path_xy(&[(100.0, 100.0), (101.0, 100.0), (102.0, 101.0)]);
+        assert!(frechet_similarity(&a, &far) < 0.05);
+    }
+
+    #[test]
+    fn sinuosity_straight_versus_semicircle()
+    {
+        let straight = path_xy(&[(0.0, 0.0), (1.0, 0.0), (3.0, 0.0)]);
+        assert!((straight.sinuosity() - 1.0).abs() < 1e-6);
+        let mut points = Vec::new();
+        for i in 0..1001 {
+            let angle = PI * (i as f32) / 1000.0;
+            points.push((angle.cos(), angle.sin()));
+        }
+        assert!((path_xy(&points).sinuosity() - PI / 2.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn chaikin_doubles_vertices_and_keeps_ends()
+    {
+        let path = path_xy(&[(0.0, 0.0), (2.0, 0.0), (2.0, 2.0)]);
+        assert_eq!(path.chaikin(1).points().len(), 6);
+        let smoothed = path.chaikin(2);
+        assert_eq!(smoothed.points().len(), 12);
+        assert_eq!(smoothed.points()[0], xy(0.0, 0.0));
+        assert_eq!(smoothed.points()[11], xy(2.0, 2.0));
+    }
+
+    #[test]
+    fn sample_scalar_interpolates_values()
+    {
+        let path = path_xy(&[(0.0, 0.0), (4.0, 0.0)]);
+        assert_eq!(path.sample_scalar(&[0.0, 10.0], 3),
+                   Ok(vec![0.0, 5.0, 10.0]));
+        assert_eq!(path.sample_scalar(&[0.0], 3),
+                   Err(PathError::LengthMismatch { expected: 2, found: 1 }));
+    }
+
+    #[test]
+    fn feret_diameters_2d_of_elongated_path()
+    {
+        let path = path_xy(&[(0.0, 0.0), (10.0, 0.0), (10.0, 1.0), (0.0, 1.0)]);
+        let (min, max) = path.feret_diameters_2d(360);
+        assert!((min - 1.0).abs() < 1e-4);
+        assert!(max >= 10.0 && max <= 101.0f32.sqrt() + 1e-4);
+    }
+
+    #[test]
+    fn orientation_frames_turn_steadily()
+    {
+        let path = path_xy(&[(0.0, 0.0), (2.0, 0.0), (2.0, 2.0)]);
+        let orientations = path.orientation_frames(21);
+        assert_eq!(orientations.len(), 21);
+        let x = Vec3::new(1.0, 0.0, 0.0);
+        let mut previous = 0.0;
+        for orientation in &orientations {
+            let direction = *orientation * x;
+            assert!(direction.z.abs() < 1e-5);
+            let angle = direction.y.atan2(direction.x);
+            assert!(angle >= previous - 1e-5);
+            previous = angle;
+        }
+        assert!((previous - PI / 2.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn least_significant_vertex_is_near_collinear()
+    {
+        let path = path_xy(&[(0.0, 0.0), (1.0, 1.0), (2.0, 0.0), (3.0, 0.05),
+                             (4.0, 0.0), (5.0, 2.0)]);
+        assert_eq!(path.least_significant_vertex(), Some(3));
+        let line = path_xy(&[(0.0, 0.0), (1.0, 1.0)]);
+        assert_eq!(line.least_significant_vertex(), None);
+    }
+
+    #[test]
+    fn simplify_visvalingam_removes_jitter()
+    {
+        // jitter of 0.1 along a line with a real peak at x = 3
+        let path = path_xy(&[(0.0, 0.0), (1.0, 0.1), (2.0, -0.1), (3.0, 2.0),
+                             (4.0, 0.1), (5.0, 0.0), (6.0, 0.0)]);
+        let simplified = path.simplify_visvalingam(0.5);
+        assert_eq!(simplified.points(),
+                   &[xy(0.0, 0.0), xy(2.0, -0.1), xy(3.0, 2.0), xy(4.0, 0.1),
+                     xy(6.0, 0.0)][..]);
+    }
+
+    #[test]
+    fn tangent_angle_profile_unwraps_angles()
+    {
+        // one and a half turns around the unit circle
+        let mut points = Vec::new();
+        for i in 0..41 {
+            let angle = 3.0 * PI * (i as f32) / 40.0;
+            points.push((angle.cos(), angle.sin()));
+        }
+        let profile = path_xy(&points).tangent_angle_profile(100);
+        assert!(profile.windows(2).all(|pair| {
+            pair[1].1 >= pair[0].1 - 1e-5 && pair[1].1 - pair[0].1 < 0.3
+        }));
+        assert!(profile[99].1 - profile[0].1 > 8.0);
+    }
+
+    #[test]
+    fn turning_function_distance_scaled_versus_different()
+    {
+        let a = path_xy(&[(0.0, 0.0), (2.0, 0.0), (2.0, 1.0), (3.0, 1.0)]);
+        // rotated by 90 degrees, scaled by 2 and moved
+        let scaled = path_xy(&[(5.0, 5.0), (5.0, 9.0), (3.0, 9.0),
+                               (3.0, 11.0)]);
+        assert!(turning_function_distance(&a, &scaled) < 1e-4);
+        let other = path_xy(&[(0.0, 0.0), (1.0, 0.0), (1.0, 3.0), (3.0, 3.0)]);
+        assert!(turning_function_distance(&a, &other) > 0.1);
+    }
+
+    #[test]
+    fn resample_by_turning_clusters_on_curve()
+    {
+        // a straight approach followed by a half circle
+        let mut points = vec![(-10.0, -1.0), (-8.0, -1.0), (-6.0, -1.0),
+                              (-4.0, -1.0), (-2.0, -1.0)];
+        for i in 0..21 {
+            let angle = -PI / 2.0 + PI * (i as f32) / 20.0;
+            points.push((angle.cos(), angle.sin()));
+        }
+        let path = path_xy(&points);
+        let resampled = path.resample_by_turning(0.3);
+        let on_straight = resampled.points().iter()
+            .filter(|point| point.x < -1.0)
+            .count();
+        let on_curve = resampled.points().iter()
+            .filter(|point| point.x >= 0.0)
+            .count();
+        assert_eq!(on_straight, 1);
+        assert!(on_curve >= 8);
+    }
+
+    #[test]
+    fn normalized_vertices_map_corners()
+    {
+        let path = path_xyz(&[(-1.0, 2.0, 3.0), (3.0, 4.0, 5.0),
+                              (1.0, 3.0, 4.0)]);
+        let normalized = path.normalized_vertices();
+        assert_eq!(normalized[0], Vec4::new(0.0, 0.0, 0.0, 1.0));
+        assert_eq!(normalized[1], Vec4::new(1.0, 1.0, 1.0, 1.0));
+        assert_eq!(normalized[2], Vec4::new(0.5, 0.5, 0.5, 1.0));
+    }
+
+    #[test]
+    fn open_at_quarter_of_closed_square()
+    {
+        let square = path_xy(&[(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)]);
+        let opened = square.open_at(0.25);
+        let points = opened.points();
+        assert_eq!(points[0], xy(1.0, 0.0));
+        assert_eq!(points[points.len()-1], xy(1.0, 0.0));
+        assert!((opened.length_streaming() - 4.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn bending_energy_straight_versus_bent()
+    {
+        let straight = path_xy(&[(0.0, 0.0), (1.0, 0.0), (2.0, 0.0)]);
+        assert!(straight.bending_energy().abs() < 1e-6);
+        let bent = path_xy(&[(0.0, 0.0), (1.0, 0.0), (2.0, 1.0)]);
+        assert!(bent.bending_energy() > 0.0);
+    }
+
+    #[test]
+    fn ribbon_2d_edges_are_width_apart()
+    {
+        let path = path_xy(&[(0.0, 0.0), (2.0, 0.0), (5.0, 0.0)]);
+        let (left, right) = path.ribbon_2d(2.0, 6);
+        for (l, r) in left.iter().zip(&right) {
+            assert!((l.x - r.x).abs() < 1e-6);
+            assert!((l.y - 1.0).abs() < 1e-6 && (r.y + 1.0).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn radius_of_gyration_of_symmetric_points()
+    {
+        // distances 3, 1, 1, 3 from the centroid: sqrt((9+1+1+9)/4)
+        let path = path_xy(&[(-3.0, 0.0), (-1.0, 0.0), (1.0, 0.0), (3.0, 0.0)]);
+        assert!((path.radius_of_gyration() - 5.0f32.sqrt()).abs() < 1e-5);
+    }
+
+    #[test]
+    fn evaluate_bspline_closed_of_square()
+    {
+        let square = path_xy(&[(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)]);
+        let points = square.evaluate_bspline_closed(3, 101);
+        assert_eq!(points.len(), 101);
+        assert!(na::norm(&(points[0] - points[100])) < 1e-5);
+        assert!(points.iter().all(|point| {
+            point.x > 0.0 && point.x < 1.0 && point.y > 0.0 && point.y < 1.0
+        }));
+        // (P3 + 4 P0 + P1) / 6 at the start
+        assert!(na::norm(&(points[0] - xy(1.0 / 6.0, 1.0 / 6.0))) < 1e-5);
+    }
+
+    #[test]
+    fn min_turning_radius_at_tight_corner()
+    {
+        // three points on a circle of radius 0.5 around the origin
+        let path = path_xy(&[(-5.0, 0.0), (-0.5, 0.0), (0.0, 0.5), (0.5, 0.0),
+                             (5.0, 0.0)]);
+        assert!((path.min_turning_radius() - 0.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn chord_parameters_of_triangle()
+    {
+        let path = path_xy(&[(0.0, 0.0), (3.0, 0.0), (3.0, 4.0), (0.0, 0.0)]);
+        let params = path.chord_parameters();
+        let expected = [0.0, 0.25, 7.0 / 12.0, 1.0];
+        for (param, expected) in params.iter().zip(&expected) {
+            assert!((param - expected).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn find_near_coincidences_pairs_start_and_middle()
+    {
+        let path = path_xy(&[(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.001, 0.0),
+                             (-1.0, 0.0), (-1.0, -0.0005)]);
+        // the last two points are neighbours and don't count
+        assert_eq!(path.find_near_coincidences(0.01), vec![(0, 3)]);
+    }
+
+    #[test]
+    fn segment_length_fractions_sum_to_one()
+    {
+        let path = path_xy(&[(0.0, 0.0), (1.0, 0.0), (1.0, 2.0), (4.0, 2.0)]);
+        let fractions = path.segment_length_fractions();
+        assert_eq!(fractions.len(), 3);
+        for (fraction, expected) in fractions.iter().zip(&[1.0, 2.0, 3.0]) {
+            assert!((fraction - expected / 6.0).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn finalize_compact_releases_capacity()
+    {
+        let mut builder = PathBuilder::new();
+        for i in 0..10 {
+            builder.add_sorted_point(xy(i as f32, 0.0), i as f32);
+        }
+        let path = builder.finalize_compact();
+        assert_eq!(path.points.capacity(), path.points.len());
+        let params = path.params.as_ref().unwrap();
+        assert_eq!(params.capacity(), params.len());
+    }
+
+    #[test]
+    fn symmetric_difference_length_2d_of_overlapping_squares()
+    {
+        // 2 of the 8 boundary units of each square lie inside the other
+        let a = path_xy(&[(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)]);
+        let b = path_xy(&[(1.0, 1.0), (3.0, 1.0), (3.0, 3.0), (1.0, 3.0)]);
+        assert!((symmetric_difference_length_2d(&a, &b) - 12.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn symmetric_difference_length_2d_of_squares_sharing_an_edge()
+    {
+        // the shared edge belongs to neither of the two differences
+        let a = path_xy(&[(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)]);
+        let b = path_xy(&[(2.0, 0.0), (4.0, 0.0), (4.0, 2.0), (2.0, 2.0)]);
+        assert!((symmetric_difference_length_2d(&a, &b) - 12.0).abs() < 1e-5);
+        assert_eq!(symmetric_difference_length_2d(&a, &a), 0.0);
+    }
+
+    #[test]
+    fn resample_jittered_is_deterministic_and_on_path()
+    {
+        let path = path_xy(&[(0.0, 0.0), (5.0, 0.0), (5.0, 5.0)]);
+        let points = path.resample_jittered(1.0, 0.3, 42);
+        assert_eq!(points, path.resample_jittered(1.0, 0.3, 42));
+        assert_ne!(points, path.resample_jittered(1.0, 0.3, 43));
+        assert_eq!(points.len(), 11);
+        for (i, point) in points.iter().enumerate() {
+            assert!(path.signed_distance_2d(*point).abs() < 1e-5);
+            let offset = path.progress_distance(*point) - i as f32;
+            assert!(offset.abs() <= 0.3 + 1e-4);
+        }
+    }
+
+    #[test]
+    fn overlap_length_2d_of_there_and_back()
+    {
+        // the last 6 units run back along the first segment
+        let path = path_xy(&[(0.0, 0.0), (10.0, 0.0), (4.0, 0.01)]);
+        assert!((path.overlap_length_2d(0.1) - 6.0).abs() < 0.3);
+        let square = path_xy(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0),
+                               (0.0, 10.0)]);
+        assert_eq!(square.overlap_length_2d(0.1), 0.0);
+    }
+
+    #[test]
+    fn retime_halves_density_on_slow_segment()
+    {
+        // the second segment takes twice as long as the first one
+        let path = path_xy(&[(0.0, 0.0), (1.0, 0.0), (2.0, 0.0)]);
+        let points = path.retime(&[0.0, 1.0, 3.0], 7).unwrap();
+        assert_eq!(points.iter().filter(|point| point.x < 1.0).count(), 2);
+        assert_eq!(points.iter().filter(|point| point.x > 1.0).count(), 4);
+        assert!((points[4].x - 1.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn longest_straight_run_finds_middle_section()
+    {
+        let path = path_xy(&[(0.0, 0.0), (1.0, 1.0), (2.0, 0.0), (4.0, 0.01),
+                             (7.0, 0.0), (9.0, 0.02), (9.5, 2.0), (10.0, 0.0)]);
+        let (start, end, length) = path.longest_straight_run(0.05);
+        assert_eq!((start, end), (2, 5));
+        assert!((length - path.length_between(2, 5)).abs() < 1e-5);
+    }
+
+    #[test]
+    fn partition_equal_length_into_three()
+    {
+        let path = path_xy(&[(0.0, 0.0), (5.0, 0.0), (5.0, 7.0)]);
+        let parts = path.partition_equal_length(3);
+        assert_eq!(parts.len(), 3);
+        for part in &parts {
+            assert!((part.length_streaming() - 4.0).abs() < 1e-5);
+        }
+        assert_eq!(parts[1].points(), &[xy(4.0, 0.0), xy(5.0, 0.0),
+                                        xy(5.0, 3.0)][..]);
+    }
+
+    #[test]
+    fn sanitize_drops_nan_and_duplicate()
+    {
+        let mut path = path_xy(&[(0.0, 0.0), (1.0, 0.0), (f32::NAN, 0.0),
+                                 (1.0, 0.0), (1.0, 2.0)]);
+        let report = path.sanitize(1e-6);
+        assert_eq!(report, SanitizeReport { non_finite: 1, duplicates: 1 });
+        assert_eq!(path.points(),
+                   &[xy(0.0, 0.0), xy(1.0, 0.0), xy(1.0, 2.0)][..]);
+        assert_eq!(path.cached_length(), Some(3.0));
     }
 }